@@ -1,25 +1,230 @@
 use colored::Colorize;
 use core::fmt;
-use std::path::PathBuf;
+use lazy_static::lazy_static;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+use crate::error::TemplateError;
+use crate::sanitize::{cut_end, SanitizePolicy};
+use crate::script::Script;
+use crate::template::Template;
+
+lazy_static! {
+    static ref RE_TRAILING_YEAR: Regex = Regex::new(r"\s*[\[(（]\s?(?:19|20)\d{2}\s?[\])）]$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum LANG {
     EN,
+    /// Japanese written in Latin letters, like `Oshi no Ko`.
+    ROMAJI,
     JP,
     CN,
 }
+
+impl FromStr for LANG {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "en" => Ok(LANG::EN),
+            "romaji" | "ro" => Ok(LANG::ROMAJI),
+            "jp" | "ja" => Ok(LANG::JP),
+            "cn" | "zh" => Ok(LANG::CN),
+            other => Err(format!("unknown title language `{}`, expected cn, en, romaji or jp", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VideoCodec {
+    AVC,
+    HEVC,
+    AV1,
+    VP9,
+}
+
+impl fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VideoCodec::AVC => "AVC",
+            VideoCodec::HEVC => "HEVC",
+            VideoCodec::AV1 => "AV1",
+            VideoCodec::VP9 => "VP9",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SubtitleLang {
+    CHS,
+    CHT,
+    JP,
+    EN,
+}
+
+/// How the subtitles are delivered: burnt in (`内嵌`), muxed into the
+/// container (`内封`) or shipped as separate files (`外挂`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SubtitleFormat {
+    Hardsub,
+    Softsub,
+    External,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Subtitle {
+    pub languages: Vec<SubtitleLang>,
+    pub format: Option<SubtitleFormat>,
+}
+
+impl Subtitle {
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty() && self.format.is_none()
+    }
+
+    pub fn contains(&self, lang: SubtitleLang) -> bool {
+        self.languages.contains(&lang)
+    }
+
+    pub fn is_hardsub(&self) -> bool {
+        self.format == Some(SubtitleFormat::Hardsub)
+    }
+}
+
+/// An episode number, possibly fractional like the `12.5` recap episodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpisodeNumber {
+    pub number: u32,
+    /// Hundredths after the decimal point, e.g. `50` for `12.5` and `5`
+    /// for `12.05`, so numbers compare the way they read.
+    pub fraction: Option<u32>,
+}
+
+impl From<u32> for EpisodeNumber {
+    fn from(number: u32) -> Self {
+        EpisodeNumber {
+            number,
+            fraction: None,
+        }
+    }
+}
+
+impl fmt::Display for EpisodeNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the width applies to the integer part only, so `{:02}` gives `06.5`
+        let width = f.width().unwrap_or(0);
+        write!(f, "{:0width$}", self.number, width = width)?;
+        match self.fraction {
+            Some(fraction) if fraction % 10 == 0 => write!(f, ".{}", fraction / 10),
+            Some(fraction) => write!(f, ".{:02}", fraction),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Which episodes a release covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "episodes", rename_all = "snake_case"))]
+pub enum EpisodeSpec {
+    Single(EpisodeNumber),
+    /// Inclusive range, e.g. `[01-12]`.
+    Range(u32, u32),
+    /// A complete collection (`合集`, `全集`) without explicit numbers.
+    Collection,
+}
+
+impl fmt::Display for EpisodeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpisodeSpec::Single(ep) => write!(f, "E{:02}", ep),
+            EpisodeSpec::Range(start, end) => write!(f, "E{:02}-E{:02}", start, end),
+            EpisodeSpec::Collection => Ok(()),
+        }
+    }
+}
+
+impl EpisodeSpec {
+    pub fn is_batch(&self) -> bool {
+        !matches!(self, EpisodeSpec::Single(_))
+    }
+
+    pub fn first(&self) -> Option<u32> {
+        match self {
+            EpisodeSpec::Single(ep) => Some(ep.number),
+            EpisodeSpec::Range(ep, _) => Some(*ep),
+            EpisodeSpec::Collection => None,
+        }
+    }
+}
+
+/// Regular episodes versus the various kinds of specials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EpisodeKind {
+    Regular,
+    Special,
+    OVA,
+    OAD,
+    Movie,
+    Recap,
+}
+
+impl fmt::Display for EpisodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EpisodeKind::Regular => "Episode",
+            EpisodeKind::Special => "Special",
+            EpisodeKind::OVA => "OVA",
+            EpisodeKind::OAD => "OAD",
+            EpisodeKind::Movie => "Movie",
+            EpisodeKind::Recap => "Recap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl EpisodeKind {
+    pub fn is_special(&self) -> bool {
+        *self != EpisodeKind::Regular
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BangumiTitle {
     cn: Option<String>,
     en: Option<String>,
+    romaji: Option<String>,
     jp: Option<String>,
+    /// Every title found in the release name, in order.
+    alternates: Vec<String>,
+    /// Series name from the title aliases, preferred over every language.
+    canonical: Option<String>,
 }
 
 impl fmt::Display for BangumiTitle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "{} {} {}",
+            "{} {} {} {}",
             self.get_title(LANG::CN).bright_green(),
             self.get_title(LANG::EN).bright_blue(),
+            self.get_title(LANG::ROMAJI).bright_magenta(),
             self.get_title(LANG::JP).bright_yellow(),
         )
     }
@@ -27,13 +232,44 @@ impl fmt::Display for BangumiTitle {
 
 impl BangumiTitle {
     pub fn new(cn: Option<String>, en: Option<String>, jp: Option<String>) -> Self {
-        BangumiTitle { cn, en, jp }
+        BangumiTitle {
+            cn,
+            en,
+            romaji: None,
+            jp,
+            alternates: Vec::new(),
+            canonical: None,
+        }
+    }
+
+    pub fn with_romaji(mut self, romaji: Option<String>) -> Self {
+        self.romaji = romaji;
+        self
+    }
+
+    pub fn with_alternates(mut self, alternates: Vec<String>) -> Self {
+        self.alternates = alternates;
+        self
+    }
+
+    pub fn alternates(&self) -> &[String] {
+        &self.alternates
+    }
+
+    pub fn with_canonical(mut self, canonical: Option<String>) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    pub fn canonical(&self) -> Option<&str> {
+        self.canonical.as_deref()
     }
 
     pub fn get_title(&self, lang: LANG) -> &str {
         let opt_title = match lang {
             LANG::CN => &self.cn,
             LANG::EN => &self.en,
+            LANG::ROMAJI => &self.romaji,
             LANG::JP => &self.jp,
         };
         if let Some(title) = opt_title {
@@ -42,29 +278,202 @@ impl BangumiTitle {
         ""
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.cn.is_none() && self.en.is_none() && self.romaji.is_none() && self.jp.is_none()
+    }
+
     pub fn get_default_title(&self) -> &str {
-        self.cn
-            .as_deref()
-            .or(self.en.as_deref())
-            .or(self.jp.as_deref())
+        self.get_preferred_title(&DEFAULT_TITLE_LANGS)
+    }
+
+    /// The title in the first of `langs` that is present, then in the
+    /// default order. A canonical name from the aliases always wins.
+    pub fn get_preferred_title(&self, langs: &[LANG]) -> &str {
+        if let Some(canonical) = &self.canonical {
+            return canonical;
+        }
+        langs
+            .iter()
+            .chain(&DEFAULT_TITLE_LANGS)
+            .map(|&lang| self.get_title(lang))
+            .find(|title| !title.is_empty())
             .unwrap_or("Unknown")
     }
+
+    /// The preferred title, with a CN title converted to the configured
+    /// script so both spellings of a show share one folder. A `(2023)` the
+    /// release put in the title is dropped when the options name the year
+    /// on their own.
+    pub fn get_named_title(&self, options: &NamingOptions) -> String {
+        let title = self.get_preferred_title(&options.title_langs);
+        let title = options.strip_year(title);
+        let title = title.as_ref();
+        match options.script {
+            Some(script) if self.canonical.is_none() && self.cn.as_deref() == Some(title) => {
+                script.convert(title)
+            }
+            _ => title.to_owned(),
+        }
+    }
+}
+
+/// Title languages by preference when none are configured.
+const DEFAULT_TITLE_LANGS: [LANG; 4] = [LANG::CN, LANG::EN, LANG::ROMAJI, LANG::JP];
+
+/// How output names are built.
+#[derive(Debug, Clone)]
+pub struct NamingOptions {
+    /// Title languages by preference.
+    pub title_langs: Vec<LANG>,
+    /// Script of CN titles, `None` keeps them as released.
+    pub script: Option<Script>,
+    /// Name series folders `Title (2022)` when the year is known, which
+    /// media servers use to tell remakes apart.
+    pub year_in_folder: bool,
+    /// Layout replacing the built-in one.
+    pub template: Option<Template>,
+    /// Applied to every folder and file name generated.
+    pub sanitize: SanitizePolicy,
+}
+
+impl NamingOptions {
+    /// Whether the year is added to names by `year_in_folder` or a
+    /// `{year}` placeholder.
+    fn names_year(&self) -> bool {
+        self.year_in_folder || self.template.as_ref().is_some_and(Template::uses_year)
+    }
+
+    /// The same options with the built-in layout.
+    fn without_template(&self) -> Self {
+        NamingOptions {
+            template: None,
+            ..self.clone()
+        }
+    }
+
+    /// `title` without a trailing `(2023)` when the year is named anyway.
+    pub(crate) fn strip_year<'a>(&self, title: &'a str) -> Cow<'a, str> {
+        match self.names_year() {
+            true => RE_TRAILING_YEAR.replace(title, ""),
+            false => Cow::Borrowed(title),
+        }
+    }
+}
+
+impl Default for NamingOptions {
+    fn default() -> Self {
+        NamingOptions {
+            title_langs: DEFAULT_TITLE_LANGS.to_vec(),
+            script: None,
+            year_in_folder: false,
+            template: None,
+            sanitize: SanitizePolicy::default(),
+        }
+    }
+}
+
+/// Something about a parse that makes its result doubtful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseWarning {
+    BareEpisodeNumber,
+    NoGroupBracket,
+    TitleFromFallbackLanguage,
+    ShortTitle,
+    MovieFallback,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ParseWarning::BareEpisodeNumber => "episode inferred from bare number",
+            ParseWarning::NoGroupBracket => "no group bracket",
+            ParseWarning::TitleFromFallbackLanguage => "title from fallback language",
+            ParseWarning::ShortTitle => "title is a single short token",
+            ParseWarning::MovieFallback => "no episode token, parsed as a movie",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl ParseWarning {
+    fn penalty(&self) -> f32 {
+        match self {
+            ParseWarning::BareEpisodeNumber => 0.3,
+            ParseWarning::NoGroupBracket => 0.15,
+            // plenty of good releases carry no Chinese title at all
+            ParseWarning::TitleFromFallbackLanguage => 0.0,
+            ParseWarning::ShortTitle => 0.3,
+            ParseWarning::MovieFallback => 0.2,
+        }
+    }
 }
 
+/// How much a parse can be trusted, from `0.0` to `1.0`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Confidence {
+    pub score: f32,
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl Default for Confidence {
+    fn default() -> Self {
+        Confidence {
+            score: 1.0,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl Confidence {
+    pub fn from_warnings(warnings: Vec<ParseWarning>) -> Self {
+        let penalty: f32 = warnings.iter().map(|w| w.penalty()).sum();
+        Confidence {
+            score: (1.0 - penalty).max(0.0),
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bangumi {
     pub title: BangumiTitle,
     pub season: u32,
-    pub episode: u32,
+    pub episode: EpisodeSpec,
+    pub kind: EpisodeKind,
     pub group: String,
+    /// Members of a collaboration, or just `group` for a single team.
+    pub groups: Vec<String>,
     pub extension: Option<String>,
+    /// Vertical resolution, e.g. `1080` for `1080p` or `1920x1080`.
+    pub resolution: Option<u32>,
+    pub video_codec: Option<VideoCodec>,
+    pub bit_depth: Option<u8>,
+    /// Streaming platform (`Baha`, `CR`, ...) or rip type (`WebRip`, `BD`, ...).
+    pub source: Option<String>,
+    pub subtitle: Subtitle,
+    /// Release revision, e.g. `2` for `[03v2]`. `None` for the original release.
+    pub version: Option<u32>,
+    pub confidence: Confidence,
+    pub year: Option<u32>,
+    /// Broadcast quarter, 1 for January shows up to 4 for October ones.
+    pub quarter: Option<u32>,
+    /// Checksum tagged in the release name, to verify the download.
+    pub crc32: Option<u32>,
+    /// Marked as the last episode of the season.
+    pub is_final: bool,
+    /// Part of an episode split across files.
+    pub part: Option<u32>,
 }
 
 impl fmt::Display for Bangumi {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let sp = format!("S{:02}E{:02}", &self.season, &self.episode);
-        let group = match self.group.len() > 0 {
-            true => format!("{}", self.group),
-            false => String::from("Unknown"),
+        let sp = self.episode_tag();
+        let group = match self.group.is_empty() {
+            false => self.group.to_owned(),
+            true => String::from("Unknown"),
         };
         write!(
             f,
@@ -80,7 +489,7 @@ impl Bangumi {
     pub fn new(
         title: BangumiTitle,
         season: u32,
-        episode: u32,
+        episode: EpisodeSpec,
         group: String,
         extension: Option<String>,
     ) -> Self {
@@ -88,14 +497,75 @@ impl Bangumi {
             title,
             season,
             episode,
+            kind: EpisodeKind::Regular,
+            groups: vec![group.to_owned()],
             group,
             extension,
+            resolution: None,
+            video_codec: None,
+            bit_depth: None,
+            source: None,
+            subtitle: Subtitle::default(),
+            version: None,
+            confidence: Confidence::default(),
+            year: None,
+            quarter: None,
+            crc32: None,
+            is_final: false,
+            part: None,
+        }
+    }
+
+    /// Whether this release supersedes an earlier one of the same episode.
+    pub fn is_revision(&self) -> bool {
+        self.version.is_some_and(|v| v > 1)
+    }
+
+    /// `S02E05` for regular episodes. Specials all live in season 0, so the
+    /// kind and original season are kept to tell them apart.
+    pub fn episode_tag(&self) -> String {
+        match self.kind {
+            EpisodeKind::Regular => format!("S{:02}{}", self.season, self.episode),
+            kind if self.season > 1 => {
+                format!("S00{} - {} S{:02}", self.episode, kind, self.season)
+            }
+            kind => format!("S00{} - {}", self.episode, kind),
+        }
+    }
+
+    /// The season media servers file the episode under, 0 for specials.
+    pub fn library_season(&self) -> u32 {
+        match self.kind.is_special() {
+            true => 0,
+            false => self.season,
         }
     }
-    pub fn gen_filename(&self) -> String {
-        let group = match self.group.len() > 0 {
-            true => format!("{}", self.group),
-            false => String::from("Unknown"),
+
+    pub fn season_dir(&self) -> String {
+        match self.kind.is_special() {
+            true => String::from("Specials"),
+            false => format!("Season {}", self.season),
+        }
+    }
+
+    /// The file name, in the default layout when the template needs a value
+    /// the release lacks. [`Bangumi::try_gen_filename`] reports that instead.
+    pub fn gen_filename(&self, options: &NamingOptions) -> String {
+        self.try_gen_filename(options)
+            .unwrap_or_else(|_| self.gen_filename(&options.without_template()))
+    }
+
+    /// The file name, or the placeholder the template needs but the release
+    /// has no value for.
+    pub fn try_gen_filename(&self, options: &NamingOptions) -> Result<String, TemplateError> {
+        if let Some(template) = &options.template {
+            let mut segments = template.render(self, options)?;
+            return Ok(options.sanitize.apply(&segments.pop().unwrap_or_default()));
+        }
+
+        let group = match self.group.is_empty() {
+            false => self.group.to_owned(),
+            true => String::from("Unknown"),
         };
 
         let ext = match &self.extension {
@@ -103,21 +573,63 @@ impl Bangumi {
             None => ""
         };
 
-        format!(
-            "{} - S{:02}E{:02} - {}{}",
-            self.title.get_default_title(),
-            self.season,
-            self.episode,
-            group,
-            ext
-        )
+        // kept in the name so a later release can tell which one is newer
+        let version = match self.version {
+            Some(v) if self.is_revision() => format!(" [v{}]", v),
+            _ => String::new(),
+        };
+
+        let title = self.title.get_named_title(options);
+        let filename = options.sanitize.fit(|cut| {
+            format!(
+                "{} - {} - {}{}{}",
+                cut_end(&title, cut),
+                self.episode_tag(),
+                group,
+                version,
+                ext
+            )
+        });
+        Ok(options.sanitize.apply(&filename))
     }
 
-    pub fn gen_fullpath(&self, dest: &PathBuf, group: bool) -> PathBuf {
+    /// Name of the series folder, `Title (2022)` with `year_in_folder`.
+    pub fn series_dir(&self, options: &NamingOptions) -> String {
+        let title = self.title.get_named_title(options);
+        match self.year {
+            Some(year) if options.year_in_folder => format!("{} ({})", title, year),
+            _ => title,
+        }
+    }
+
+    /// The full path, in the default layout when the template needs a value
+    /// the release lacks. [`Bangumi::try_gen_fullpath`] reports that instead.
+    pub fn gen_fullpath(&self, dest: &Path, group: bool, options: &NamingOptions) -> PathBuf {
+        self.try_gen_fullpath(dest, group, options)
+            .unwrap_or_else(|_| self.gen_fullpath(dest, group, &options.without_template()))
+    }
+
+    /// The full path, or the placeholder the template needs but the release
+    /// has no value for.
+    pub fn try_gen_fullpath(
+        &self,
+        dest: &Path,
+        group: bool,
+        options: &NamingOptions,
+    ) -> Result<PathBuf, TemplateError> {
+        if let (Some(template), true) = (&options.template, group) {
+            let segments = template.render(self, options)?;
+            return Ok(segments.iter().fold(dest.to_path_buf(), |path, segment| {
+                path.join(options.sanitize.apply(segment))
+            }));
+        }
         if group {
-            dest.join(self.title.get_default_title()).join(format!("Season {}", self.season)).join(self.gen_filename())
+            Ok(dest
+                .join(options.sanitize.apply(&self.series_dir(options)))
+                .join(self.season_dir())
+                .join(self.try_gen_filename(options)?))
         } else {
-            dest.join(self.gen_filename())
+            Ok(dest.join(self.try_gen_filename(options)?))
         }
     }
 }
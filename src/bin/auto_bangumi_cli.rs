@@ -1,12 +1,23 @@
-use auto_bangumi_rs::parser::Parser as BangumiParser;
+use auto_bangumi_rs::{
+    bangumi::{Bangumi, EpisodeSpec, NamingOptions, LANG},
+    parser::{parse_version, Parser as BangumiParser},
+    preset::Preset,
+    rules::Rules,
+    sanitize::SanitizePolicy,
+    script::Script,
+    template::Template,
+};
 use std::{
-    fs::{self, create_dir_all},
+    fs::{self, create_dir_all, File},
+    io::{ErrorKind, IsTerminal, Read},
     path::{Path, PathBuf},
+    process::exit,
 };
 
-use colored::Colorize;
+use colored::{ColoredString, Colorize};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
 
 #[derive(Parser)]
 #[command(author, version, about="A bangumi (anime) renamer authored by _connlost.", long_about = None, arg_required_else_help = true)]
@@ -19,17 +30,207 @@ struct Cli {
     dryrun: bool,
     #[arg(short, long, help = "Group animes by series and season")]
     group_by_name: bool,
+    #[arg(long, value_name = "DIRECTORY", help = "Send files parsed with low confidence, or as a batch, here, keeping their names")]
+    review_dir: Option<PathBuf>,
+    #[arg(long, value_name = "SCORE", default_value_t = 0.6, help = "Confidence below which files go to --review-dir")]
+    min_confidence: f32,
+    #[arg(long, value_name = "FILE", help = "TOML file with extra parsing rules")]
+    rules: Option<PathBuf>,
+    #[arg(long, value_name = "LANGS", value_delimiter = ',', default_value = "cn,en,romaji,jp", help = "Title languages by preference, e.g. en,romaji,cn")]
+    title_lang: Vec<LANG>,
+    #[arg(long, value_name = "SCRIPT", help = "Write Chinese titles in simplified or traditional characters")]
+    script: Option<Script>,
+    #[arg(long, help = "Name series folders \"Title (Year)\" when the year is known")]
+    year_in_folder: bool,
+    #[arg(long, value_name = "TEMPLATE", help = "Output layout, e.g. \"{title}< ({year})>/Season {season}/{title} - S{season:02}E{episode:02} - {group}{ext}\"")]
+    template: Option<Template>,
+    #[arg(long, value_name = "SERVER", conflicts_with = "template", help = "Layout of a media server: jellyfin, plex, kodi, emby or infuse, used over a template from --rules. Implies --group-by-name")]
+    preset: Option<Preset>,
+    #[arg(long, help = "Replace characters Windows forbids in names with full-width lookalikes instead of dropping them")]
+    fullwidth: bool,
+    #[arg(long, help = "Check files against the CRC32 in their name, e.g. [ABCD1234], and skip corrupted ones")]
+    verify_crc: bool,
+    #[arg(long, value_name = "DIRECTORY", requires = "verify_crc", help = "Send files failing --verify-crc here instead of skipping them")]
+    quarantine_dir: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = Format::Plain, help = "How to report each file: plain, json (an array once done) or ndjson (a line per file)")]
+    format: Format,
     #[command(subcommand)]
-    mode: Mode,
+    command: Command,
 }
 
 #[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Rename(Mode),
+    #[command(about = "Show how a title is parsed, step by step")]
+    Explain { title: String },
+}
+
+/// How files are put at their new path.
+#[derive(Subcommand, Clone, Copy)]
 enum Mode {
     Move,
     Copy,
     HardLink,
 }
 
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Plain,
+    Json,
+    Ndjson,
+}
+
+/// What happened to one file, as printed by `--format json|ndjson`.
+struct Record {
+    source: PathBuf,
+    destination: Option<PathBuf>,
+    bangumi: Option<Bangumi>,
+    action: Action,
+    status: Status,
+    /// An older revision of the episode was, or would be, replaced.
+    replaced: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Move,
+    Copy,
+    HardLink,
+    /// Sent to `--review-dir` for low confidence.
+    Review,
+    /// Sent to `--quarantine-dir` for a CRC mismatch.
+    Quarantine,
+    Skip,
+}
+
+#[derive(Clone, Copy)]
+enum Status {
+    Done,
+    DryRun,
+    Skipped,
+    Failed,
+}
+
+impl Record {
+    fn skipped(source: &Path, bangumi: Option<Bangumi>, error: String) -> Self {
+        Record {
+            source: source.to_owned(),
+            destination: None,
+            bangumi,
+            action: Action::Skip,
+            status: Status::Skipped,
+            replaced: false,
+            error: Some(error),
+        }
+    }
+
+    fn failed(source: &Path, bangumi: Option<Bangumi>, error: String) -> Self {
+        Record {
+            status: Status::Failed,
+            ..Record::skipped(source, bangumi, error)
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let action = match self.action {
+            Action::Move => "move",
+            Action::Copy => "copy",
+            Action::HardLink => "hard_link",
+            Action::Review => "review",
+            Action::Quarantine => "quarantine",
+            Action::Skip => "skip",
+        };
+        let status = match self.status {
+            Status::Done => "done",
+            Status::DryRun => "dry_run",
+            Status::Skipped => "skipped",
+            Status::Failed => "failed",
+        };
+        json!({
+            "source": self.source,
+            "destination": self.destination,
+            "bangumi": self.bangumi.as_ref().map(bangumi_json),
+            "action": action,
+            "status": status,
+            "replaced": self.replaced,
+            "error": self.error,
+        })
+    }
+}
+
+/// The parsed fields, laid out like the library's `serde` feature does
+/// without depending on it.
+fn bangumi_json(bangumi: &Bangumi) -> Value {
+    let title = |lang| Some(bangumi.title.get_title(lang)).filter(|t| !t.is_empty());
+    let name = |value: &dyn std::fmt::Debug| format!("{:?}", value);
+    let episode = match bangumi.episode {
+        EpisodeSpec::Single(ep) => json!({
+            "type": "single",
+            "episodes": {"number": ep.number, "fraction": ep.fraction},
+        }),
+        EpisodeSpec::Range(start, end) => json!({"type": "range", "episodes": [start, end]}),
+        EpisodeSpec::Collection => json!({"type": "collection"}),
+    };
+    json!({
+        "title": {
+            "cn": title(LANG::CN),
+            "en": title(LANG::EN),
+            "romaji": title(LANG::ROMAJI),
+            "jp": title(LANG::JP),
+            "alternates": bangumi.title.alternates(),
+            "canonical": bangumi.title.canonical(),
+        },
+        "season": bangumi.season,
+        "episode": episode,
+        "kind": name(&bangumi.kind),
+        "group": bangumi.group,
+        "groups": bangumi.groups,
+        "extension": bangumi.extension,
+        "resolution": bangumi.resolution,
+        "video_codec": bangumi.video_codec.map(|codec| name(&codec)),
+        "bit_depth": bangumi.bit_depth,
+        "source": bangumi.source,
+        "subtitle": {
+            "languages": bangumi.subtitle.languages.iter().map(|lang| name(lang)).collect::<Vec<_>>(),
+            "format": bangumi.subtitle.format.map(|format| name(&format)),
+        },
+        "version": bangumi.version,
+        "confidence": {
+            "score": bangumi.confidence.score,
+            "warnings": bangumi.confidence.warnings.iter().map(|w| name(w)).collect::<Vec<_>>(),
+        },
+        "year": bangumi.year,
+        "quarter": bangumi.quarter,
+        "crc32": bangumi.crc32,
+        "is_final": bangumi.is_final,
+        "part": bangumi.part,
+    })
+}
+
+/// Styling for a message on stderr, which may be redirected while stdout
+/// is not.
+fn on_stderr(text: ColoredString) -> ColoredString {
+    match std::io::stderr().is_terminal() {
+        true => text,
+        false => text.clear(),
+    }
+}
+
+/// Styling for a line on stdout, which may be piped while stderr is not.
+fn on_stdout(text: ColoredString) -> ColoredString {
+    match std::io::stdout().is_terminal() {
+        true => text,
+        false => text.clear(),
+    }
+}
+
+/// Whether colors are wanted at all, going by `NO_COLOR` and `CLICOLOR=0`.
+fn colors_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::env::var_os("CLICOLOR").is_none_or(|v| v != "0")
+}
+
 fn collect_files(paths: &Vec<PathBuf>) -> Vec<PathBuf> {
     let mut files = Vec::new();
 
@@ -37,35 +238,17 @@ fn collect_files(paths: &Vec<PathBuf>) -> Vec<PathBuf> {
         if !bangumi_path.exists() {
             eprintln!(
                 "Path {} does not exist!",
-                bangumi_path.to_string_lossy().green()
+                on_stderr(bangumi_path.to_string_lossy().green())
             );
             continue;
         }
 
         match bangumi_path {
             _ if bangumi_path.is_file() => files.push(bangumi_path.to_owned()),
-            _ if bangumi_path.is_dir() => {
-                if let Ok(reader) = fs::read_dir(&bangumi_path) {
-                    for entry in reader {
-                        match entry {
-                            Ok(file) => {
-                                let path_buf = file.path();
-                                if path_buf.is_file() {
-                                    files.push(path_buf)
-                                }
-                            }
-                            Err(e) => eprintln!(
-                                "Error traversing directory {}, {}",
-                                bangumi_path.to_string_lossy().green(),
-                                e
-                            ),
-                        }
-                    }
-                }
-            }
+            _ if bangumi_path.is_dir() => collect_dir(bangumi_path, &mut files),
             _ => eprintln!(
                 "Error occured with provided path {}",
-                bangumi_path.to_string_lossy().green()
+                on_stderr(bangumi_path.to_string_lossy().green())
             ),
         }
     }
@@ -73,83 +256,548 @@ fn collect_files(paths: &Vec<PathBuf>) -> Vec<PathBuf> {
     files
 }
 
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(reader) = fs::read_dir(dir) {
+        for entry in reader {
+            match entry {
+                Ok(file) => {
+                    let path_buf = file.path();
+                    if path_buf.is_file() {
+                        files.push(path_buf)
+                    } else if path_buf.is_dir() && is_batch_dir(&path_buf) {
+                        collect_dir(&path_buf, files)
+                    }
+                }
+                Err(e) => eprintln!(
+                    "Error traversing directory {}, {}",
+                    on_stderr(dir.to_string_lossy().green()),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Batch releases (`[01-12]`, `合集`...) come as a folder of episodes.
+fn is_batch_dir(dir: &Path) -> bool {
+    dir.file_name()
+        .and_then(|name| BangumiParser::new(name.to_string_lossy().into_owned()).ok())
+        .and_then(|parser| parser.episode_spec())
+        .is_some_and(|spec| spec.is_batch())
+}
+
 fn try_read_season_from_dir(parent: &Path) -> Option<u32> {
     let dot_season_path = parent.join(".season");
-    return fs::read_to_string(dot_season_path)
+    fs::read_to_string(dot_season_path)
         .ok()?
         .trim()
         .parse::<u32>()
-        .ok();
+        .ok()
+}
+
+fn place_file(src: &Path, dst: &Path, mode: &Mode) -> Result<(), std::io::Error> {
+    match mode {
+        Mode::Move => fs::rename(src, dst),
+        Mode::Copy => match fs::copy(src, dst) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        },
+        Mode::HardLink => fs::hard_link(src, dst),
+    }
 }
 
+/// Returns whether an existing file was replaced.
 fn rename_file(
     src: &PathBuf,
     dst: &PathBuf,
     mode: &Mode,
     dryrun: bool,
-) -> Result<(), std::io::Error> {
-    println!(
-        "- {} \n\t=> {}",
-        src.to_string_lossy().bright_yellow(),
-        dst.to_string_lossy().bright_blue(),
-    );
+    version: Option<u32>,
+) -> Result<bool, std::io::Error> {
+    // only a newer revision (v2, v3...) of the same episode may take over an existing file
+    let existing = find_release(dst);
+    if let Some(existing) = &existing {
+        let existing_version = existing
+            .file_name()
+            .and_then(|name| parse_version(&name.to_string_lossy()));
+        if version.unwrap_or(1) <= existing_version.unwrap_or(1) {
+            return Err(std::io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} already exists", existing.to_string_lossy()),
+            ));
+        }
+    }
     if dryrun {
-        return Ok(());
+        return Ok(existing.is_some());
     }
 
     if let Some(folder) = dst.parent() {
         if let Err(e) = create_dir_all(folder) {
             eprint!(
                 "Error reanme file {}: {}",
-                src.to_string_lossy().green(),
-                e.to_string().red()
+                on_stderr(src.to_string_lossy().green()),
+                on_stderr(e.to_string().red())
             )
         }
     }
 
-    match mode {
-        Mode::Move => fs::rename(src, dst),
-        Mode::Copy => match fs::copy(src, dst) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
-        },
-        Mode::HardLink => fs::hard_link(src, dst),
+    let Some(existing) = existing else {
+        place_file(src, dst, mode)?;
+        return Ok(false);
+    };
+
+    // the old version stays until the new one is complete next to it, so a
+    // failed copy or a cross-device move loses nothing
+    let mut staged = dst.as_os_str().to_owned();
+    staged.push(".part");
+    let staged = PathBuf::from(staged);
+    if let Err(e) = place_file(src, &staged, mode) {
+        let _ = fs::remove_file(&staged);
+        return Err(e);
     }
+    if let Err(e) = fs::rename(&staged, dst) {
+        let _ = match mode {
+            Mode::Move => fs::rename(&staged, src),
+            _ => fs::remove_file(&staged),
+        };
+        return Err(e);
+    }
+    if existing != *dst {
+        fs::remove_file(existing)?;
+    }
+    Ok(true)
 }
 
-fn _sanitize_filename(filename: &str) -> String {
-    let sanitized: String = filename
-        .chars()
-        .filter(|&c| !"<>:\"/\\|?*\0".contains(c))
-        .collect();
+/// `name` without the ` [vN]` tag [`Bangumi::gen_filename`] adds to
+/// revisions.
+fn without_version(name: &str) -> String {
+    if let Some(start) = name.rfind(" [v") {
+        if let Some(len) = name[start + 3..].find(']') {
+            let digits = &name[start + 3..start + 3 + len];
+            if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                return format!("{}{}", &name[..start], &name[start + 4 + len..]);
+            }
+        }
+    }
+    name.to_owned()
+}
 
-    sanitized
+/// The file already holding this episode: `dst` itself, or another
+/// revision of it named the same apart from the version tag.
+fn find_release(dst: &Path) -> Option<PathBuf> {
+    if dst.exists() {
+        return Some(dst.to_path_buf());
+    }
+    let name = without_version(&dst.file_name()?.to_string_lossy());
+    fs::read_dir(dst.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|other| without_version(&other.to_string_lossy()) == name)
+        })
 }
 
-fn process_files(paths: Vec<PathBuf>, cli: &Cli) {
-    for path in paths {
-        let season = path.parent().and_then(try_read_season_from_dir);
-        if let Some(bangumi) =
-            BangumiParser::from_path(&path).and_then(|parser| parser.to_bangumi(season))
-        {
-            let output_path = match &cli.output {
-                Some(output) => output.to_owned(),
-                None => path.parent().unwrap().to_path_buf(),
+fn file_crc32(path: &Path) -> Result<u32, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(hasher.finalize()),
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+}
+
+/// Checks the file against the CRC32 tagged in its name, returning the
+/// mismatch if any. Files without a tag pass.
+fn verify_crc(path: &Path, expected: Option<u32>) -> Result<Option<String>, std::io::Error> {
+    let Some(expected) = expected else {
+        return Ok(None);
+    };
+    let actual = file_crc32(path)?;
+    Ok((actual != expected).then(|| {
+        format!("CRC mismatch, expected {:08X}, got {:08X}", expected, actual)
+    }))
+}
+
+fn process_file(
+    path: &Path,
+    cli: &Cli,
+    mode: Mode,
+    rules: &Rules,
+    options: &NamingOptions,
+    group_by_name: bool,
+) -> Record {
+    let season = path.parent().and_then(try_read_season_from_dir);
+    let bangumi = match BangumiParser::from_path_with_rules(path, rules)
+        .and_then(|parser| parser.to_bangumi(season))
+    {
+        Ok(bangumi) => bangumi,
+        Err(e) => return Record::skipped(path, None, e.to_string()),
+    };
+    // one file covering `[01-12]` or `合集` is more likely a misparse than a
+    // batch, batches come as directories
+    let batch = bangumi.episode.is_batch();
+    if batch && cli.review_dir.is_none() {
+        return Record::skipped(
+            path,
+            Some(bangumi),
+            "batch releases should be a directory".to_owned(),
+        );
+    }
+
+    let output_path = match &cli.output {
+        Some(output) => output.to_owned(),
+        None => path.parent().unwrap().to_path_buf(),
+    };
+
+    let crc_error = match cli.verify_crc {
+        true => match verify_crc(path, bangumi.crc32) {
+            Ok(mismatch) => mismatch,
+            Err(e) => {
+                return Record::failed(path, Some(bangumi), format!("cannot hash file: {}", e))
+            }
+        },
+        false => None,
+    };
+    let (action, out_path) = match (&cli.quarantine_dir, &cli.review_dir) {
+        (Some(quarantine_dir), _) if crc_error.is_some() => (
+            Action::Quarantine,
+            quarantine_dir.join(path.file_name().unwrap()),
+        ),
+        (None, _) if crc_error.is_some() => {
+            return Record::skipped(path, Some(bangumi), crc_error.unwrap())
+        }
+        (_, Some(review_dir)) if batch || bangumi.confidence.score < cli.min_confidence => {
+            (Action::Review, review_dir.join(path.file_name().unwrap()))
+        }
+        _ => {
+            let action = match mode {
+                Mode::Move => Action::Move,
+                Mode::Copy => Action::Copy,
+                Mode::HardLink => Action::HardLink,
             };
+            // a file in the default layout would split the library in two
+            match bangumi.try_gen_fullpath(&output_path, group_by_name, options) {
+                Ok(out_path) => (action, out_path),
+                Err(e) => {
+                    return Record::skipped(path, Some(bangumi), format!("cannot apply the template: {}", e))
+                }
+            }
+        }
+    };
+
+    let result = rename_file(
+        &path.to_path_buf(),
+        &out_path,
+        &mode,
+        cli.dryrun,
+        bangumi.version,
+    );
+    let (status, replaced, error) = match result {
+        Ok(replaced) if cli.dryrun => (Status::DryRun, replaced, crc_error),
+        Ok(replaced) => (Status::Done, replaced, crc_error),
+        Err(e) => (Status::Failed, false, Some(e.to_string())),
+    };
+    Record {
+        source: path.to_owned(),
+        destination: Some(out_path),
+        bangumi: Some(bangumi),
+        action,
+        status,
+        replaced,
+        error,
+    }
+}
+
+fn print_plain(record: &Record) {
+    let source = record.source.to_string_lossy();
+    if let Some(bangumi) = &record.bangumi {
+        for warning in &bangumi.confidence.warnings {
+            eprintln!(
+                "Warning {}: {}",
+                on_stderr(source.green()),
+                on_stderr(warning.to_string().yellow())
+            );
+        }
+    }
+    match (record.status, &record.destination, &record.error) {
+        (Status::Skipped, _, Some(e)) => {
+            eprintln!("Skipping {}: {}", on_stderr(source.green()), on_stderr(e.red()))
+        }
+        (Status::Failed, destination, Some(e)) => match destination {
+            Some(destination) => eprintln!(
+                "Failed {} => {}: {}",
+                on_stderr(source.green()),
+                on_stderr(destination.to_string_lossy().green()),
+                on_stderr(e.red())
+            ),
+            None => eprintln!("Failed {}: {}", on_stderr(source.green()), on_stderr(e.red())),
+        },
+        (_, Some(destination), error) => {
+            if let Some(e) = error {
+                eprintln!(
+                    "{} {}: {}",
+                    on_stderr("Quarantining".red()),
+                    on_stderr(source.green()),
+                    on_stderr(e.red())
+                );
+            }
+            println!(
+                "- {} \n\t=> {}{}",
+                on_stdout(source.bright_yellow()),
+                on_stdout(destination.to_string_lossy().bright_blue()),
+                on_stdout(match record.replaced {
+                    true => " (replacing older version)".bright_red(),
+                    false => "".normal(),
+                })
+            )
+        }
+        _ => (),
+    }
+}
+
+fn process_files(paths: Vec<PathBuf>, cli: &Cli, mode: Mode, rules: &Rules) {
+    if let (Some(_), Some(template)) = (cli.preset, &rules.template) {
+        eprintln!(
+            "Warning: --preset replaces the rules template {}",
+            on_stderr(template.source().yellow())
+        );
+    }
+    let options = NamingOptions {
+        title_langs: cli.title_lang.to_owned(),
+        script: cli.script,
+        year_in_folder: cli.year_in_folder,
+        template: cli
+            .preset
+            .map(|preset| preset.template())
+            .or(cli.template.to_owned())
+            .or(rules.template.to_owned()),
+        sanitize: SanitizePolicy {
+            fullwidth: cli.fullwidth,
+            ..SanitizePolicy::default()
+        },
+    };
+    let group_by_name = cli.group_by_name || cli.preset.is_some();
+    let mut records = Vec::new();
+    for path in paths {
+        let record = process_file(&path, cli, mode, rules, &options, group_by_name);
+        match cli.format {
+            Format::Plain => print_plain(&record),
+            Format::Ndjson => println!("{}", record.to_json()),
+            Format::Json => records.push(record.to_json()),
+        }
+    }
+    if cli.format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&records).unwrap());
+    }
+}
 
-            let out_path = bangumi.gen_fullpath(&output_path, cli.group_by_name);
-            match rename_file(&path, &out_path, &cli.mode, cli.dryrun) {
-                Ok(_) => (),
-                Err(e) => eprintln!("{}", e),
+fn explain(title: &str, rules: &Rules) {
+    match BangumiParser::with_trace(title.to_owned(), rules) {
+        Ok(parser) => {
+            print!("{}", parser.explain());
+            match parser.to_bangumi(None) {
+                Ok(bangumi) => println!("{:<24} {}", "result".bright_cyan(), bangumi),
+                Err(e) => println!("{:<24} {}", "result".bright_cyan(), e.to_string().red()),
             }
-        } else {
-            eprintln!("Skipping {}", path.to_string_lossy().green());
+        }
+        Err(e) => println!("{}", e.to_string().red()),
+    }
+}
+
+fn load_rules(path: &Option<PathBuf>) -> Rules {
+    let Some(path) = path else {
+        return Rules::default();
+    };
+    match Rules::from_file(path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!(
+                "Error loading rules {}: {}",
+                on_stderr(path.to_string_lossy().green()),
+                on_stderr(e.to_string().red())
+            );
+            exit(1);
         }
     }
 }
 
 fn main() {
     let cli = Cli::parse();
-    let files = collect_files(&cli.input);
-    process_files(files, &cli);
+    // escape codes would end up in JSON strings, pipes and log files are
+    // left to `on_stdout` and `on_stderr`
+    colored::control::set_override(cli.format == Format::Plain && colors_enabled());
+    let rules = load_rules(&cli.rules);
+    match &cli.command {
+        Command::Explain { title } => {
+            // the trace is styled by the library, and all of it goes to stdout
+            if !std::io::stdout().is_terminal() {
+                colored::control::set_override(false);
+            }
+            explain(title, &rules)
+        }
+        Command::Rename(mode) => {
+            let files = collect_files(&cli.input);
+            process_files(files, &cli, *mode, &rules);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        find_release, process_file, rename_file, without_version, Cli, Command, Mode, Record,
+    };
+    use auto_bangumi_rs::{bangumi::NamingOptions, rules::Rules};
+    use clap::Parser as _;
+    use serde_json::json;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process,
+    };
+
+    /// An empty folder of its own for each test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("auto_bangumi_{}_{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_without_version() {
+        let cases = [
+            ("Horimiya - S01E07 - LoliHouse [v3].mkv", "Horimiya - S01E07 - LoliHouse.mkv"),
+            ("Horimiya - S01E07 - LoliHouse.mkv", "Horimiya - S01E07 - LoliHouse.mkv"),
+            ("Horimiya [vol] - S01E07 - LoliHouse.mkv", "Horimiya [vol] - S01E07 - LoliHouse.mkv"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(expected, without_version(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_find_release() {
+        let dir = scratch("find_release");
+        let dst = dir.join("Horimiya - S01E07 - LoliHouse.mkv");
+        assert_eq!(None, find_release(&dst));
+
+        let revision = dir.join("Horimiya - S01E07 - LoliHouse [v3].mkv");
+        fs::write(&revision, "v3").unwrap();
+        fs::write(dir.join("Horimiya - S01E08 - LoliHouse.mkv"), "08").unwrap();
+        assert_eq!(Some(revision), find_release(&dst));
+
+        fs::write(&dst, "v1").unwrap();
+        assert_eq!(Some(dst), find_release(&dir.join("Horimiya - S01E07 - LoliHouse.mkv")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rename_file() {
+        let dir = scratch("rename_file");
+        let original = dir.join("Horimiya - S01E07 - LoliHouse.mkv");
+        let revision = dir.join("Horimiya - S01E07 - LoliHouse [v2].mkv");
+        let incoming = dir.join("incoming.mkv");
+
+        // a v2 replaces the v1
+        fs::write(&original, "v1").unwrap();
+        fs::write(&incoming, "v2").unwrap();
+        assert!(rename_file(&incoming, &revision, &Mode::Copy, false, Some(2)).unwrap());
+        assert!(!original.exists());
+        assert_eq!("v2", fs::read_to_string(&revision).unwrap());
+
+        // a v1 arriving late leaves the v2 alone
+        fs::write(&incoming, "v1").unwrap();
+        let err = rename_file(&incoming, &original, &Mode::Copy, false, None).unwrap_err();
+        assert_eq!(std::io::ErrorKind::AlreadyExists, err.kind());
+        assert!(!original.exists());
+        assert_eq!("v2", fs::read_to_string(&revision).unwrap());
+
+        // a failed copy keeps the file it was meant to replace
+        let v3 = dir.join("Horimiya - S01E07 - LoliHouse [v3].mkv");
+        let missing = dir.join("missing.mkv");
+        assert!(rename_file(&missing, &v3, &Mode::Copy, false, Some(3)).is_err());
+        assert!(!v3.exists());
+        assert_eq!("v2", fs::read_to_string(&revision).unwrap());
+        // nothing staged is left behind
+        assert_eq!(vec!["Horimiya - S01E07 - LoliHouse [v2].mkv", "incoming.mkv"], file_names(&dir));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_bangumi_json() {
+        use auto_bangumi_rs::parser::Parser;
+
+        let titles = [
+            "[SubsPlease] Urusei Yatsura (2022) - 05 (1080p) [ABCD1234].mkv",
+            "[LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 12.5 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+            "[Nekomoe kissaten][Jujutsu Kaisen][03v2][1080p][CHS].mp4",
+            "[LoliHouse] 福星小子 / Urusei Yatsura OVA 01 [WebRip 1080p HEVC-10bit AAC].mkv",
+            "[SubsPlease] Sousou no Frieren - 28 Fin (1080p) [ABCD1234].mkv",
+            "[Sakurato] Spy x Family [01-12][1080p][CHS]",
+            "[VCB-Studio] 葬送的芙莉莲 合集 [Ma10p_1080p]",
+            "Sousou no Frieren 05 [1080p].mkv",
+        ];
+        for title in titles {
+            let bangumi = Parser::new(title.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+            assert_eq!(serde_json::to_value(&bangumi).unwrap(), super::bangumi_json(&bangumi), "{}", title);
+        }
+    }
+
+    #[test]
+    fn test_record_json() {
+        let dir = scratch("record_json");
+        let file = dir.join("[SubsPlease] Sousou no Frieren - 05 (1080p).mkv");
+        fs::write(&file, "05").unwrap();
+        let output = dir.join("out");
+        let cli = Cli::parse_from([
+            "auto_bangumi_cli".as_ref(),
+            "--output".as_ref(),
+            output.as_os_str(),
+            "--dryrun".as_ref(),
+            "--format".as_ref(),
+            "ndjson".as_ref(),
+            "copy".as_ref(),
+        ]);
+        let Command::Rename(mode) = cli.command else {
+            panic!("copy is a rename");
+        };
+        let options = NamingOptions::default();
+        let record = process_file(&file, &cli, mode, &Rules::default(), &options, false);
+        let destination = output.join("Sousou no Frieren - S01E05 - SubsPlease.mkv");
+        let json = record.to_json();
+        assert_eq!(json!(file), json["source"]);
+        assert_eq!(json!(destination), json["destination"]);
+        assert_eq!("copy", json["action"]);
+        assert_eq!("dry_run", json["status"]);
+        assert_eq!(false, json["replaced"]);
+        assert_eq!(json!(null), json["error"]);
+        assert_eq!(5, json["bangumi"]["episode"]["episodes"]["number"]);
+        assert_eq!("Sousou no Frieren", json["bangumi"]["title"]["romaji"]);
+        assert!(!destination.exists());
+
+        let skipped = Record::skipped(&file, None, "no episode".to_owned()).to_json();
+        assert_eq!("skip", skipped["action"]);
+        assert_eq!("skipped", skipped["status"]);
+        assert_eq!(json!(null), skipped["bangumi"]);
+        assert_eq!("no episode", skipped["error"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -26,14 +26,9 @@ async fn test_url(url: &str) {
         let channel = Channel::read_from(bytes).unwrap();
         for item in channel.items {
             if let Some(raw_title) = item.title {
-                let parser = Parser::new(raw_title).unwrap();
-                if parser.can_parse() {
-                    match parser.to_bangumi(None) {
-                        Some(b) => println!("{}", b),
-                        None => eprintln!("{}", "FAILED".red())
-                    }
-                } else {
-                    eprintln!("{}", "FAILED".red())
+                match Parser::new(raw_title.to_owned()).and_then(|p| p.to_bangumi(None)) {
+                    Ok(b) => println!("{}", b),
+                    Err(e) => eprintln!("{} {}: {}", "FAILED".red(), raw_title, e),
                 }
             }
         }
@@ -0,0 +1,112 @@
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Why a title or file could not be turned into a [`crate::bangumi::Bangumi`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseError {
+    /// The name already follows the `Title - SxxExx - Group` output format.
+    AlreadyFormatted,
+    NoEpisode,
+    NoTitle,
+    NotAFile,
+    NoFileName,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ParseError::AlreadyFormatted => "already renamed",
+            ParseError::NoEpisode => "no episode found",
+            ParseError::NoTitle => "no title found",
+            ParseError::NotAFile => "not a file",
+            ParseError::NoFileName => "path has no file name",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why a rules file could not be loaded.
+#[derive(Debug)]
+pub enum RulesError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    /// A key that is unknown or holds the wrong type of value.
+    Invalid(String),
+    Regex(regex::Error),
+    /// A pattern lacks the named capture group it is required to have.
+    MissingCapture(String, &'static str),
+    Template(TemplateError),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "cannot read rules: {}", e),
+            RulesError::Toml(e) => write!(f, "invalid rules: {}", e),
+            RulesError::Invalid(e) => write!(f, "invalid rules: {}", e),
+            RulesError::Regex(e) => write!(f, "invalid pattern: {}", e),
+            RulesError::MissingCapture(pattern, capture) => {
+                write!(f, "pattern {} has no `{}` capture group", pattern, capture)
+            }
+            RulesError::Template(e) => write!(f, "invalid template: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl From<std::io::Error> for RulesError {
+    fn from(e: std::io::Error) -> Self {
+        RulesError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for RulesError {
+    fn from(e: toml::de::Error) -> Self {
+        RulesError::Toml(e)
+    }
+}
+
+impl From<regex::Error> for RulesError {
+    fn from(e: regex::Error) -> Self {
+        RulesError::Regex(e)
+    }
+}
+
+impl From<TemplateError> for RulesError {
+    fn from(e: TemplateError) -> Self {
+        RulesError::Template(e)
+    }
+}
+
+/// Why a naming template could not be parsed or rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    UnknownPlaceholder(String),
+    /// A format spec other than a width, or a width on a text placeholder.
+    InvalidFormat(String),
+    /// A `{` or `<` without its closing counterpart, or the other way round.
+    Unbalanced(char),
+    /// A path segment with nothing in it, as in `a//b` or a leading `/`.
+    EmptySegment,
+    /// A placeholder outside `<...>` with no value for the release.
+    MissingValue(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{}}}", name),
+            TemplateError::InvalidFormat(spec) => write!(f, "invalid format {{{}}}", spec),
+            TemplateError::Unbalanced(c) => write!(f, "unbalanced `{}`", c),
+            TemplateError::EmptySegment => write!(f, "empty path segment"),
+            TemplateError::MissingValue(name) => write!(f, "no value for {{{}}}", name),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
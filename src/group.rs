@@ -0,0 +1,78 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Spellings seen in the wild, keyed in lowercase, mapped to the name
+    /// used in filenames.
+    static ref GROUP_ALIASES: HashMap<&'static str, &'static str> = [
+        ("喵萌奶茶屋", &["喵萌", "喵萌production", "nekomoe kissaten", "nekomoe"][..]),
+        ("LoliHouse", &["lolihouse"]),
+        ("桜都字幕组", &["桜都字幕組", "樱都字幕组", "sakurato"]),
+        ("动漫国字幕组", &["動漫國字幕組", "dmg"]),
+        ("北宇治字幕组", &["北宇治字幕組", "kitauji"]),
+        ("千夏字幕组", &["千夏字幕組"]),
+        ("Lilith-Raws", &["lilith-raws", "lilith raws"]),
+        ("Skymoon-Raws", &["skymoon-raws", "skymoon raws"]),
+        ("ANi", &["ani"]),
+    ]
+    .into_iter()
+    .flat_map(|(name, aliases)| aliases.iter().map(move |alias| (*alias, name)))
+    .collect();
+}
+
+/// Splits a collaboration like `A&B` or `A×B` into its members, each
+/// normalized through the built-in aliases and then `extra`, which takes
+/// precedence.
+pub fn parse_groups(raw: &str, extra: &HashMap<String, String>) -> Vec<String> {
+    raw.split(['&', '＆', '×'])
+        .map(str::trim)
+        .filter(|member| !member.is_empty())
+        .map(|member| normalize_group(member, extra))
+        .collect()
+}
+
+/// Maps a single group name to its canonical spelling. Unknown names are
+/// kept as they are.
+pub fn normalize_group(name: &str, extra: &HashMap<String, String>) -> String {
+    let name = name.trim();
+    if let Some(canonical) = extra.get(name) {
+        return canonical.to_owned();
+    }
+    let lower = name.to_lowercase();
+    if let Some((_, canonical)) = extra.iter().find(|(alias, _)| alias.to_lowercase() == lower) {
+        return canonical.to_owned();
+    }
+    match GROUP_ALIASES.get(lower.as_str()) {
+        Some(canonical) => canonical.to_string(),
+        None => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{normalize_group, parse_groups};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_normalize_group() {
+        let extra = HashMap::from([("SweetSub".to_owned(), "SweetSub字幕组".to_owned())]);
+        let cases = [
+            ("喵萌Production", "喵萌奶茶屋"),
+            ("Nekomoe kissaten", "喵萌奶茶屋"),
+            ("LOLIHOUSE", "LoliHouse"),
+            ("sweetsub", "SweetSub字幕组"),
+            ("GJ.Y", "GJ.Y"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(expected, normalize_group(raw, &extra), "{}", raw);
+        }
+        assert_eq!(
+            vec!["LoliHouse", "喵萌奶茶屋"],
+            parse_groups("LoliHouse&喵萌", &extra)
+        );
+        assert_eq!(
+            vec!["北宇治字幕组", "LoliHouse"],
+            parse_groups("北宇治字幕組×LoliHouse", &extra)
+        );
+    }
+}
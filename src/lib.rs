@@ -1,2 +1,11 @@
 pub mod bangumi;
-pub mod parser;
\ No newline at end of file
+pub mod error;
+pub mod group;
+pub mod numeral;
+pub mod parser;
+pub mod preset;
+pub mod profile;
+pub mod rules;
+pub mod sanitize;
+pub mod script;
+pub mod template;
\ No newline at end of file
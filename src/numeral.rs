@@ -0,0 +1,131 @@
+/// Converts the numbers found in release titles to `u32`.
+///
+/// Handles ASCII and full-width Arabic digits (`12`, `１２`), compound
+/// Chinese/Japanese numerals (`十二`, `二十`, `一百零五`, `两`), the
+/// financial/daiji forms (`壹`, `弐`, `参`...) and positional numerals
+/// such as `二〇二三`.
+pub fn parse_numeral(raw: &str) -> Option<u32> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    if raw.chars().all(|c| c.is_ascii_digit() || fullwidth_digit(c).is_some()) {
+        return raw.chars().try_fold(0u32, |acc, c| {
+            let digit = fullwidth_digit(c).or_else(|| c.to_digit(10))?;
+            acc.checked_mul(10)?.checked_add(digit)
+        });
+    }
+
+    // `二〇二三` style, digits without any unit
+    if raw.chars().count() > 1 && raw.chars().all(|c| cjk_digit(c).is_some()) {
+        return raw.chars().try_fold(0u32, |acc, c| {
+            acc.checked_mul(10)?.checked_add(cjk_digit(c)?)
+        });
+    }
+
+    let mut total = 0u32;
+    let mut section = 0u32;
+    let mut digit: Option<u32> = None;
+    // units shrink within a section, so `百百` or `十百` is not a number
+    let mut last_unit = u32::MAX;
+    for c in raw.chars() {
+        if let Some(d) = cjk_digit(c) {
+            // only `零` may be followed by another digit, as in `一百零五`
+            if digit.is_some_and(|prev| prev != 0) {
+                return None;
+            }
+            digit = Some(d);
+        } else if let Some(unit) = cjk_unit(c) {
+            if unit == 10_000 {
+                // `万` multiplies everything before it, once
+                if total > 0 || last_unit == unit {
+                    return None;
+                }
+                total = section.checked_add(digit.unwrap_or(0))?.checked_mul(unit)?;
+                section = 0;
+                last_unit = unit;
+            } else {
+                if unit >= last_unit {
+                    return None;
+                }
+                // a bare `十` means ten, as in `十二`
+                section = section.checked_add(digit.unwrap_or(1).checked_mul(unit)?)?;
+                last_unit = unit;
+            }
+            digit = None;
+        } else {
+            return None;
+        }
+    }
+    total.checked_add(section)?.checked_add(digit.unwrap_or(0))
+}
+
+fn fullwidth_digit(c: char) -> Option<u32> {
+    match c {
+        '０'..='９' => Some(c as u32 - '０' as u32),
+        _ => None,
+    }
+}
+
+fn cjk_digit(c: char) -> Option<u32> {
+    match c {
+        '〇' | '零' => Some(0),
+        '一' | '壹' | '壱' => Some(1),
+        '二' | '两' | '兩' | '贰' | '貳' | '弐' => Some(2),
+        '三' | '叁' | '參' | '参' => Some(3),
+        '四' | '肆' => Some(4),
+        '五' | '伍' => Some(5),
+        '六' | '陆' | '陸' => Some(6),
+        '七' | '柒' => Some(7),
+        '八' | '捌' => Some(8),
+        '九' | '玖' => Some(9),
+        _ => None,
+    }
+}
+
+fn cjk_unit(c: char) -> Option<u32> {
+    match c {
+        '十' | '拾' => Some(10),
+        '百' | '佰' => Some(100),
+        '千' | '仟' => Some(1000),
+        '万' | '萬' => Some(10_000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_numeral;
+
+    #[test]
+    fn test_parse_numeral() {
+        let cases = [
+            ("12", Some(12)),
+            ("２", Some(2)),
+            ("１２", Some(12)),
+            ("三", Some(3)),
+            ("两", Some(2)),
+            ("十", Some(10)),
+            ("十二", Some(12)),
+            ("二十", Some(20)),
+            ("二十一", Some(21)),
+            ("一百零五", Some(105)),
+            ("二〇二三", Some(2023)),
+            ("壱", Some(1)),
+            ("一万二千", Some(12000)),
+            ("第三", None),
+            ("百百", None),
+            ("十百", None),
+            ("二三十", None),
+            ("一万万", None),
+            ("一万二千万", None),
+            ("九千九百九十九万", Some(99_990_000)),
+            ("四十三万", Some(430_000)),
+            ("", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(expected, parse_numeral(raw), "{}", raw);
+        }
+    }
+}
@@ -1,56 +1,235 @@
 use lazy_static::lazy_static;
-use regex::Regex;
-use std::{ops::Range, path::PathBuf};
+use regex::{Captures, Regex};
+use colored::Colorize;
+use core::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, path::Path};
 
-use crate::bangumi::{Bangumi, BangumiTitle};
+use crate::bangumi::{
+    Bangumi, BangumiTitle, Confidence, LANG, EpisodeKind, EpisodeNumber, EpisodeSpec, ParseWarning,
+    Subtitle, SubtitleFormat, SubtitleLang, VideoCodec,
+};
+use crate::error::ParseError;
+use crate::group::parse_groups;
+use crate::numeral::parse_numeral;
+use crate::profile::{find_profile, GroupProfile};
+use crate::rules::Rules;
 
 lazy_static! {
     static ref RE_GROUP: Regex = Regex::new(r"\[([^\]]+)\]").unwrap();
-    static ref RE_MAIN_SPLIT: Regex = Regex::new(r"(?:\[([^\]]+)\])?(?P<season>.*|\[.*])(?P<episode>\(\d{1,3}\)| -? \d+|\[\d+]|\[\d+.?[vV]\d]|第\d+[话話集]|\[第?\d+[话話集]]|\[\d+.?END]|[Ee][Pp]?\d+|\[?特[別别]篇\]?|\[?[總总]集篇\]?| \d+ |\d{1,4}-\d{1,4}|合集)(?P<others>.*)").unwrap();
-    static ref RE_EPISODE: Regex = Regex::new(r"(\d+)").unwrap();
-    static ref RE_SEASON: Regex = Regex::new(r"(\d{1,2}(?:st|nd|rd|th) Season)|(Season \d{1,2})|(S\d{1,2})|[第].[季期]|\d[季期]").unwrap();
+    static ref RE_MAIN_SPLIT: Regex = Regex::new(r"(?:\[([^\]]+)\])?(?P<season>.*|\[.*])(?P<episode>\(\d{1,3}\)| -? \d+(?:\.\d{1,2})?|\[\d{1,4}(?:\.\d{1,2})?]|\[\d+.?[vV]\d]|第[\d〇零一二两兩三四五六七八九十百千]+(?:\.\d{1,2})?[话話集回]|\[第?\d+[话話集]]|\[\d+.?END]|[Ee][Pp]?\d+|\[?特[別别]篇\]?|\[?[總总]集篇\]?|\[(?:SP|OVA|OAD)\s?\d{0,3}]| (?:SP|OVA|OAD)\s?\d{0,3}\b| \d+ |\[\d{1,4}-\d{1,4}(?:\s?(?:END|Fin|完))?]| \d{1,4}-\d{1,4}\b|全\d+[话話集]|合集|全集)(?P<others>.*)").unwrap();
+    static ref RE_EPISODE: Regex = Regex::new(r"(\d+)(?:\.(\d{1,2}))?").unwrap();
+    static ref RE_EPISODE_CN: Regex = Regex::new(r"([〇零一二两兩三四五六七八九十百千]+)[话話集回]").unwrap();
+    static ref RE_EPISODE_RANGE: Regex = Regex::new(r"(\d{1,4})\s?-\s?(\d{1,4})").unwrap();
+    static ref RE_EPISODE_TOTAL: Regex = Regex::new(r"全(\d+)[话話集]").unwrap();
+    static ref RE_COLLECTION: Regex = Regex::new(r"合集|全集").unwrap();
+    static ref RE_MOVIE_SPLIT: Regex = Regex::new(r"^(?:\[[^\]]+\])?(?P<season>.*?(?:[剧劇][场場]版|\b[Mm]ovie\b)[^\[\(]*)(?P<episode>)(?P<others>.*)$").unwrap();
+    static ref RE_KIND: Regex = Regex::new(r"(?i)(?P<recap>[總总]集篇|\bRecap\b)|(?P<movie>[剧劇][场場]版|\bMovie\b)|(?P<ova>\bOVA\d{0,3}\b)|(?P<oad>\bOAD\d{0,3}\b)|(?P<special>特[別别]篇|特番|\bSP\d{0,3}\b|\bSpecials?\b)").unwrap();
+    static ref RE_KIND_CJK: Regex = Regex::new(r"[剧劇][场場]版|特[別别]篇|[總总]集篇|特番").unwrap();
+    static ref RE_BRACKETED: Regex = Regex::new(r"[\[(]([^\])]+)[\])]").unwrap();
+    static ref RE_KIND_TITLE: Regex = Regex::new(r"[剧劇][场場]版|\b(?:[Tt]he )?[Mm]ovie\b|\bOVA\b|\bOAD\b").unwrap();
+    static ref RE_SEASON: Regex = Regex::new(r"(\d{1,2}(?:st|nd|rd|th) Season)|(Season \d{1,2})|(S\d{1,2})|第[\d〇零一二两兩三四五六七八九十百壱弐参]+[季期部]|\d{1,2}[季期]").unwrap();
     static ref RE_SEASON_EN: Regex = Regex::new(r"Season|S").unwrap();
     static ref RE_SEASON_DIGIT: Regex = Regex::new(r"(\d{1,})").unwrap();
     static ref RE_SEASON_CN: Regex = Regex::new(r"[第 ].*[季期(部分)]|部分").unwrap();
-    static ref RE_SEASON_CN_DIGIT: Regex = Regex::new(r"([一二三四五六七八九十]{1})").unwrap();
+    static ref RE_SEASON_CN_DIGIT: Regex = Regex::new(r"([〇零一二两兩三四五六七八九十百壱弐参]+)").unwrap();
     static ref RE_LEFT_BRACKETS: Regex = Regex::new(r"\s*[【（「{]\s*").unwrap();
     static ref RE_RIGHT_BRACKETS: Regex = Regex::new(r"\s*[】）」}]\s*").unwrap();
     static ref RE_SPECIAL: Regex = Regex::new(r"[^\w\s\u4e00-\u9fff\u3040-\u309f\u30a0-\u30ff-]").unwrap();
     static ref RE_BANGUMI_CHARS: Regex = Regex::new(r"新番|月?番").unwrap();
     static ref RE_HKTW: Regex = Regex::new(r"港澳台|(?:仅[限僅]?)?(?:台[湾灣])|港澳").unwrap();
     static ref RE_TITLE_SPLIT: Regex = Regex::new(r"\/|\s{2}|-\s{2}|\]\[").unwrap();
-    static ref RE_SIDE_EMPTY_BRACKETS: Regex = Regex::new(r"^\[|\]$|\[\]").unwrap();
+    static ref RE_SIDE_EMPTY_BRACKETS: Regex = Regex::new(r"^\[|\]$|\[\]|\[$").unwrap();
     static ref RE_UNDERSCORE: Regex = Regex::new(r"_{1}").unwrap();
     static ref RE_DASH: Regex = Regex::new(r" - {1}").unwrap();
     static ref RE_JP: Regex = Regex::new(r"[\u0800-\u4e00]{2,}").unwrap();
     static ref RE_CN: Regex = Regex::new(r"[\u4e00-\u9fa5]{2,}").unwrap();
     static ref RE_EN: Regex = Regex::new(r"[a-zA-Z]{3,}").unwrap();
+    static ref RE_CJK_CHAR: Regex = Regex::new(r"[\u0800-\u9fa5]").unwrap();
+    static ref RE_LATIN_WORD: Regex = Regex::new(r"[a-zA-Z]+").unwrap();
+    static ref RE_ROMAJI_WORD: Regex = Regex::new(r"^(?:(?:[kgsztdnhbpmyrwfj]|[kgnhbpmr]y|ch|sh|ts|kk|ss|tt|pp|dd|gg|bb|tch|ssh|n[kgsztdhbpmyrwfj]|nch|nsh|nts|m[bmp])?[aiueo])+n?$").unwrap();
     static ref RE_EXT: Regex = Regex::new(r"(?P<ext>\.\w+)$").unwrap();
-    static ref RE_FORMATTED: Regex = Regex::new(r"(.*) - (S\d{2}E\d{2}) - (.*?)(\.\w+)?$").unwrap();
+    static ref RE_RESOLUTION: Regex = Regex::new(r"(?i)\b(?:(\d{3,4})p|\d{3,4}[x×](\d{3,4})|(4K|2K))\b").unwrap();
+    static ref RE_VIDEO_CODEC: Regex = Regex::new(r"(?i)\b(?:(HEVC|[xh]\.?265)|(AVC|[xh]\.?264)|(AV1)|(VP9))\b").unwrap();
+    static ref RE_BIT_DEPTH: Regex = Regex::new(r"(?i)\b(?:(8|10|12)[- ]?bits?|Hi(10)P)\b").unwrap();
+    static ref RE_SOURCE_PLATFORM: Regex = Regex::new(r"(?i)\b(Baha|B-Global|Bilibili|CR|Crunchyroll|ViuTV|Netflix|NF|AMZN|Sentai|ABEMA|HIDIVE|Disney\+?)(?:\b|$)").unwrap();
+    static ref RE_SOURCE_RIP: Regex = Regex::new(r"(?i)\b(WEB-?Rip|WEB-?DL|BD-?Rip|BDMV|Blu-?Ray|BD|DVD-?Rip|DVD|TV-?Rip|HDTV)\b").unwrap();
+    static ref RE_SUB_CJK: Regex = Regex::new(r"([简簡繁中日英]{1,4})(?:[体體](?:中文)?|(?:双语|雙語|三语|三語|内封|內封|内嵌|內嵌|外挂|外掛|字幕)+)").unwrap();
+    static ref RE_SUB_LATIN: Regex = Regex::new(r"(?i)\b(CHS|GB|CHT|BIG5|JPSC|JPTC)\b").unwrap();
+    static ref RE_SUB_FORMAT: Regex = Regex::new(r"([内內]嵌|硬字幕)|([内內]封|软字幕|軟字幕)|(外[挂掛])").unwrap();
+    static ref RE_VERSION_INNER: Regex = Regex::new(r"\d\s?[vV](\d{1,2})").unwrap();
+    static ref RE_VERSION_AFTER: Regex = Regex::new(r"^\s?[vV](\d{1,2})\b").unwrap();
+    static ref RE_VERSION_TAG: Regex = Regex::new(r"(?:^|[\d\s\[])[vV](\d{1,2})(?:$|[\]\s.])").unwrap();
+    static ref RE_FINAL: Regex = Regex::new(r"(?i)(?:\b|\d)END\b|\bFin\b|最[终終][话話回集]|(?:^|[\[\s\d])完[结結]?(?:$|[\]\s])").unwrap();
+    static ref RE_PART: Regex = Regex::new(r"(?i)\b(?:part|pt)\.?\s?(\d{1,2})\b").unwrap();
+    static ref RE_CRC32: Regex = Regex::new(r"\[([0-9A-Fa-f]{8})\]|\(([0-9A-Fa-f]{8})\)").unwrap();
+    static ref RE_BARE_EPISODE: Regex = Regex::new(r"^(?: \d+ |\(\d{1,3}\))$").unwrap();
+    static ref RE_LEADING_GROUP: Regex = Regex::new(r"^\[[^\]]+\]").unwrap();
+    static ref RE_EMPTY_BRACKETS: Regex = Regex::new(r"\[\s*\]|\(\s*\)").unwrap();
+    static ref RE_YEAR_MONTH: Regex = Regex::new(r"((?:19|20)\d{2})\s?年\s?(?:(\d{1,2}|[一二三四五六七八九十]{1,3})\s?月)?").unwrap();
+    static ref RE_MONTH_NEW: Regex = Regex::new(r"(\d{1,2}|[一二三四五六七八九十]{1,3})\s?月(?:新番|番)").unwrap();
+    static ref RE_YEAR_BRACKETED: Regex = Regex::new(r"[\[(（]\s?((?:19|20)\d{2})\s?[\])）]").unwrap();
+    static ref RE_FORMATTED: Regex = Regex::new(r"(.*) - (S\d{2}(?:E\d{2}(?:-E\d{2})?)?) - (.*?)(\.\w+)?$").unwrap();
 }
+const ROMAJI_PARTICLES: [&str; 9] = ["no", "wa", "ga", "ni", "wo", "to", "de", "mo", "ka"];
+
 pub struct Parser {
     raw: String,
     raw_season: Option<Range<usize>>,
     raw_episode: Option<Range<usize>>,
     raw_others: Option<Range<usize>>,
+    stages: Vec<(&'static str, String)>,
+    split: &'static str,
+    season_override: Option<u32>,
+    /// Text matched by a user season pattern, removed from the title.
+    season_token: Option<String>,
+    /// Year and month the show started airing, read before `新番` tags
+    /// are stripped.
+    year: Option<u32>,
+    month: Option<u32>,
+    episode_offset: i32,
+    profile: Option<&'static GroupProfile>,
+    /// The releasing group, normalized through the aliases.
+    group: Option<String>,
+    groups: Vec<String>,
+    /// Series name the title maps to in the title aliases.
+    canonical_title: Option<String>,
+}
+
+/// The outcome of parsing one title, to be stored or sent elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParseResult {
+    pub raw: String,
+    pub bangumi: Option<Bangumi>,
+    pub error: Option<ParseError>,
+}
+
+impl ParseResult {
+    pub fn new(raw: &str, rules: &Rules) -> Self {
+        let (bangumi, error) = match Parser::with_rules(raw.to_owned(), rules)
+            .and_then(|parser| parser.to_bangumi(None))
+        {
+            Ok(bangumi) => (Some(bangumi), None),
+            Err(e) => (None, Some(e)),
+        };
+        ParseResult {
+            raw: raw.to_owned(),
+            bangumi,
+            error,
+        }
+    }
+}
+
+/// Preprocessing steps, only recorded for [`Parser::with_trace`].
+struct Stages(Option<Vec<(&'static str, String)>>);
+
+impl Stages {
+    fn push(&mut self, name: &'static str, value: &str) {
+        if let Some(stages) = &mut self.0 {
+            stages.push((name, value.to_owned()));
+        }
+    }
+}
+
+/// Title tokens and the language bucket they landed in, `None` if dropped.
+pub type TitleTokens = Vec<(String, Option<LANG>)>;
+
+/// Intermediate results of a parse, see [`Parser::explain`].
+pub struct ParseTrace {
+    /// Each preprocessing step with the string it produced, empty unless
+    /// the parser was built with [`Parser::with_trace`].
+    pub stages: Vec<(&'static str, String)>,
+    /// Which regex split the preprocessed title, if any.
+    pub split: &'static str,
+    pub season: Option<(Range<usize>, String)>,
+    pub episode: Option<(Range<usize>, String)>,
+    pub others: Option<(Range<usize>, String)>,
+    pub title: Option<String>,
+    pub title_tokens: TitleTokens,
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.stages {
+            writeln!(f, "{:<24} {}", name.bright_cyan(), value)?;
+        }
+        writeln!(f, "{:<24} {}", "split".bright_cyan(), self.split)?;
+        for (name, part) in [
+            ("season", &self.season),
+            ("episode", &self.episode),
+            ("others", &self.others),
+        ] {
+            match part {
+                Some((range, value)) => {
+                    writeln!(f, "  {:<22} {:?} {}", name, range, value.bright_yellow())?
+                }
+                None => writeln!(f, "  {:<22} {}", name, "-".red())?,
+            }
+        }
+        if let Some(title) = &self.title {
+            writeln!(f, "{:<24} {}", "title".bright_cyan(), title)?;
+        }
+        for (token, lang) in &self.title_tokens {
+            let bucket = match lang {
+                Some(LANG::CN) => "CN".bright_green(),
+                Some(LANG::EN) => "EN".bright_blue(),
+                Some(LANG::ROMAJI) => "ROMAJI".bright_magenta(),
+                Some(LANG::JP) => "JP".bright_yellow(),
+                None => "dropped".red(),
+            };
+            writeln!(f, "  {:<22} {}", bucket, token)?;
+        }
+        Ok(())
+    }
 }
 
 impl Parser {
-    pub fn new(raw_title: String) -> Option<Self> {
-        match RE_FORMATTED.captures(&raw_title) {
-            Some(_) => {
-                return None
-            },
-            None => {}
+    pub fn new(raw_title: String) -> Result<Self, ParseError> {
+        Self::with_rules(raw_title, &Rules::default())
+    }
+
+    /// Parses with user-defined [`Rules`] on top of the built-in ones.
+    pub fn with_rules(raw_title: String, rules: &Rules) -> Result<Self, ParseError> {
+        Self::parse(raw_title, rules, false)
+    }
+
+    /// Like [`Parser::with_rules`], also recording every preprocessing
+    /// step for [`Parser::explain`].
+    pub fn with_trace(raw_title: String, rules: &Rules) -> Result<Self, ParseError> {
+        Self::parse(raw_title, rules, true)
+    }
+
+    fn parse(raw_title: String, rules: &Rules, trace: bool) -> Result<Self, ParseError> {
+        if RE_FORMATTED.is_match(&raw_title) {
+            return Err(ParseError::AlreadyFormatted);
         }
 
-        // println!("- Raw Title: {}", raw_title);
+        let mut stages = Stages(trace.then(Vec::new));
+        stages.push("raw", &raw_title);
+        let (year, month) = broadcast_date(&raw_title);
+
         // this looks bad but idk if there is a better way...
-        let processed = RE_LEFT_BRACKETS.replace_all(&raw_title.trim(), " [");
+        let processed = RE_LEFT_BRACKETS.replace_all(raw_title.trim(), " [");
+        stages.push("RE_LEFT_BRACKETS", &processed);
         let processed = RE_RIGHT_BRACKETS.replace_all(&processed, "] ");
         let processed = processed.trim();
+        stages.push("RE_RIGHT_BRACKETS", processed);
 
-        let binding = RE_SPECIAL.replace_all(&processed, "/");
+        let group_name = RE_GROUP.captures(processed).map(|caps| caps[1].to_owned());
+        let groups = group_name
+            .as_deref()
+            .map_or(Vec::new(), |name| parse_groups(name, &rules.group_aliases));
+        let group = (!groups.is_empty()).then(|| groups.join("&"));
+        let group_rules = group_name
+            .as_deref()
+            .and_then(|name| rules.group(name))
+            .or_else(|| rules.group(group.as_deref()?));
+        let profile = group.as_deref().and_then(find_profile);
+
+        let binding = RE_SPECIAL.replace_all(processed, "/");
+        stages.push("RE_SPECIAL", &binding);
         let mut token_group: Vec<&str> = binding.split('/').collect();
         token_group.retain(|s| !s.is_empty());
         if token_group.len() == 1 {
@@ -62,104 +241,286 @@ impl Parser {
             if RE_BANGUMI_CHARS.is_match(token) && token.chars().count() <= 5 {
                 let sub_re = Regex::new(&format!("([^\\]]?){}([^\\[]?)", token)).unwrap();
                 raw = sub_re.replace_all(&raw, "").trim().to_owned();
+                stages.push("RE_BANGUMI_CHARS", &raw);
             } else if RE_HKTW.is_match(token) {
                 let sub_re = Regex::new(&format!(".{}.", token)).unwrap();
                 raw = sub_re.replace_all(&raw, "").trim().to_owned();
+                stages.push("RE_HKTW", &raw);
+            }
+        }
+
+        let strip_tokens = group_rules.iter().flat_map(|g| &g.strip_tokens);
+        for token in rules.strip_tokens.iter().chain(strip_tokens) {
+            if raw.contains(token.as_str()) {
+                let stripped = raw.replace(token.as_str(), "");
+                raw = RE_EMPTY_BRACKETS.replace_all(&stripped, "").trim().to_owned();
+                stages.push("rules.strip_tokens", &raw);
             }
         }
 
         let mut raw_season = None;
         let mut raw_episode = None;
         let mut raw_others = None;
-        if let Some(caps) = RE_MAIN_SPLIT.captures(&raw) {
-            if let Some(raw) = caps.name("season") {
-                raw_season = Some(raw.range());
-            }
-            if let Some(raw) = caps.name("episode") {
-                raw_episode = Some(raw.range());
+        let episode_patterns = group_rules.iter().flat_map(|g| &g.episode_patterns);
+        let custom_caps = episode_patterns
+            .chain(&rules.episode_patterns)
+            .find_map(|re| re.captures(&raw).filter(|caps| caps.name("episode").is_some()));
+
+        let profile_caps = profile
+            .and_then(|p| p.split.as_ref())
+            .and_then(|re| re.captures(&raw));
+
+        let mut split = "RE_MAIN_SPLIT";
+        if let Some(caps) = custom_caps.as_ref().or(profile_caps.as_ref()) {
+            split = match custom_caps.is_some() {
+                true => "rules.episode_patterns",
+                false => "GroupProfile.split",
+            };
+            // user patterns usually only capture the episode, so the rest of
+            // the title is split around the whole match
+            let whole = caps.get(0).unwrap().range();
+            let group_end = RE_LEADING_GROUP.find(&raw).map_or(0, |m| m.end());
+            raw_season = Some(match caps.name("season") {
+                Some(m) => m.range(),
+                None => group_end.min(whole.start)..whole.start,
+            });
+            raw_episode = caps.name("episode").map(|m| m.range());
+            raw_others = Some(match caps.name("others") {
+                Some(m) => m.range(),
+                None => whole.end..raw.len(),
+            });
+        } else {
+            // `Part 2` is not episode 2, hide it without moving any offsets
+            let masked = RE_PART.replace_all(&raw, |caps: &Captures| caps[0].replace(' ', "_"));
+            // movies rarely carry an episode token, so fall back to the title keyword
+            let caps = RE_MAIN_SPLIT.captures(&masked).or_else(|| {
+                split = "RE_MOVIE_SPLIT";
+                RE_MOVIE_SPLIT.captures(&raw)
+            });
+            if caps.is_none() {
+                split = "none";
             }
-            if let Some(raw) = caps.name("others") {
-                raw_others = Some(raw.range());
+            if let Some(caps) = caps {
+                if let Some(raw) = caps.name("season") {
+                    raw_season = Some(raw.range());
+                }
+                if let Some(raw) = caps.name("episode") {
+                    raw_episode = Some(raw.range());
+                }
+                if let Some(raw) = caps.name("others") {
+                    raw_others = Some(raw.range());
+                }
             }
-        } else {
         }
 
-        Some(Parser {
+        let season_scope = raw_season.to_owned().unwrap_or(0..raw.len());
+        let mut season_token = None;
+        let mut season_override = rules
+            .season_patterns
+            .iter()
+            // an optional `season` group can match without taking part
+            .find_map(|re| {
+                re.captures(&raw[season_scope.to_owned()])
+                    .filter(|caps| caps.name("season").is_some())
+            })
+            .and_then(|caps| {
+                season_token = Some(caps[0].to_owned());
+                parse_numeral(&caps["season"])
+            });
+        if let Some(season) = group_rules.and_then(|g| g.season) {
+            season_override = Some(season);
+        }
+
+        let mut parser = Parser {
             raw,
             raw_season,
             raw_episode,
             raw_others,
-        })
+            stages: stages.0.unwrap_or_default(),
+            split,
+            season_override,
+            season_token,
+            episode_offset: group_rules.map_or(0, |g| g.episode_offset),
+            profile,
+            group,
+            groups,
+            canonical_title: None,
+            year,
+            month,
+        };
+        parser.canonical_title = parser
+            .title()
+            .and_then(|title| rules.canonical_title(&title).map(str::to_owned));
+        Ok(parser)
+    }
+
+    /// Describes how the title was parsed, stage by stage.
+    pub fn explain(&self) -> ParseTrace {
+        let part = |range: &Option<Range<usize>>| {
+            range
+                .as_ref()
+                .map(|r| (r.to_owned(), self.raw[r.to_owned()].to_owned()))
+        };
+        let (title, title_tokens) = match self.title_tokens() {
+            Some((title, tokens)) => (
+                Some(title),
+                tokens,
+            ),
+            None => (None, Vec::new()),
+        };
+        ParseTrace {
+            stages: self.stages.to_owned(),
+            split: self.split,
+            season: part(&self.raw_season),
+            episode: part(&self.raw_episode),
+            others: part(&self.raw_others),
+            title,
+            title_tokens,
+        }
     }
 
-    pub fn from_path(path: &PathBuf) -> Option<Self> {
+    pub fn from_path(path: &Path) -> Result<Self, ParseError> {
+        Self::from_path_with_rules(path, &Rules::default())
+    }
+
+    pub fn from_path_with_rules(path: &Path, rules: &Rules) -> Result<Self, ParseError> {
         if !path.is_file() {
-            return None;
+            return Err(ParseError::NotAFile);
         }
         if let Some(filename) = path.file_name() {
             let name = filename.to_string_lossy().as_ref().to_owned();
-            return Self::new(name);
+            return Self::with_rules(name, rules);
         }
-        None
+        Err(ParseError::NoFileName)
     }
 
+    /// The releasing group with known aliases normalized, collaborations
+    /// joined by `&`.
     pub fn group(&self) -> Option<&str> {
-        if let Some(caps) = RE_GROUP.captures(&self.raw) {
-            if let Some(m) = caps.get(1) {
-                return Some(m.as_str());
-            }
-        }
-        None
+        self.group.as_deref()
+    }
+
+    /// Each member of a collaboration such as `A&B` or `A×B`.
+    pub fn groups(&self) -> &[String] {
+        &self.groups
     }
 
     pub fn title(&self) -> Option<BangumiTitle> {
-        match &self.raw_season {
-            Some(range) => {
-                let raw_title = RE_SEASON.replace_all(&self.raw[range.to_owned()], "");
-                let raw_title = RE_SIDE_EMPTY_BRACKETS.replace_all(raw_title.trim(), "");
-                let raw_title = raw_title.trim();
-
-                let mut tokens: Vec<&str> =
-                    RE_TITLE_SPLIT.split(&raw_title).map(|s| s.trim()).collect();
-
-                if tokens.len() == 1 {
-                    tokens = match raw_title {
-                        _ if RE_UNDERSCORE.is_match(tokens[0]) => {
-                            split_and_trim(&RE_UNDERSCORE, tokens[0])
-                        }
-                        _ if RE_DASH.is_match(tokens[0]) => split_and_trim(&RE_DASH, tokens[0]),
-                        _ => tokens,
-                    };
-                }
+        let (_, tokens) = self.title_tokens()?;
 
-                let mut list_jp = Vec::new();
-                let mut list_cn = Vec::new();
-                let mut list_en = Vec::new();
-
-                for token in tokens {
-                    match token {
-                        // Do not change the order!
-                        _ if RE_JP.is_match(token) => &mut list_jp,
-                        _ if RE_CN.is_match(token) => &mut list_cn,
-                        _ if RE_EN.is_match(token) => &mut list_en,
-                        _ => {
-                            continue;
-                        }
-                    }
-                    .push(token);
-                }
+        let mut list_jp = Vec::new();
+        let mut list_cn = Vec::new();
+        let mut list_en = Vec::new();
+        let mut list_romaji = Vec::new();
 
-                return Some(BangumiTitle::new(
-                    join_and_clean(list_cn),
-                    join_and_clean(list_en),
-                    join_and_clean(list_jp),
-                ));
+        for (token, lang) in &tokens {
+            match lang {
+                Some(LANG::JP) => &mut list_jp,
+                Some(LANG::CN) => &mut list_cn,
+                Some(LANG::EN) => &mut list_en,
+                Some(LANG::ROMAJI) => &mut list_romaji,
+                None => continue,
             }
-            None => None,
+            .push(token.as_str());
         }
+
+        let alternates = tokens
+            .iter()
+            .filter(|(_, lang)| lang.is_some())
+            .filter_map(|(token, _)| join_and_clean(vec![token]))
+            .collect();
+
+        let title = BangumiTitle::new(
+            join_and_clean(list_cn),
+            join_and_clean(list_en),
+            join_and_clean(list_jp),
+        );
+        Some(
+            title
+                .with_romaji(join_and_clean(list_romaji))
+                .with_alternates(alternates)
+                .with_canonical(self.canonical_title.to_owned()),
+        )
+    }
+
+    /// The cleaned title part and its tokens, each with the language it is
+    /// classified as.
+    fn title_tokens(&self) -> Option<(String, TitleTokens)> {
+        let range = self.raw_season.as_ref()?;
+        let mut raw_title = self.raw[range.to_owned()].to_owned();
+        if let Some(token) = &self.season_token {
+            raw_title = raw_title.replace(token.as_str(), "");
+        }
+        let raw_title = RE_SEASON.replace_all(&raw_title, "");
+        let raw_title = RE_KIND_TITLE.replace_all(&raw_title, "");
+        let raw_title = RE_SIDE_EMPTY_BRACKETS.replace_all(raw_title.trim(), "");
+        let raw_title = raw_title.trim();
+
+        let mut tokens: Vec<&str> = RE_TITLE_SPLIT.split(raw_title).map(|s| s.trim()).collect();
+
+        let mut dash_split = false;
+        if tokens.len() == 1 {
+            tokens = match raw_title {
+                _ if RE_UNDERSCORE.is_match(tokens[0]) => split_and_trim(&RE_UNDERSCORE, tokens[0]),
+                _ if RE_DASH.is_match(tokens[0]) => {
+                    dash_split = true;
+                    split_and_trim(&RE_DASH, tokens[0])
+                }
+                _ => tokens,
+            };
+        }
+        // Latin parts around a dash are usually one title and its subtitle,
+        // `Kaguya-sama wa Kokurasetai - First Kiss wa Owaranai`, so they
+        // share a language
+        let latin: Vec<&str> = tokens
+            .iter()
+            .copied()
+            .filter(|token| RE_EN.is_match(token) && !RE_CJK_CHAR.is_match(token))
+            .collect();
+        let romaji_title = (dash_split && latin.len() > 1).then(|| is_romaji(&latin.join(" ")));
+
+        let order = self
+            .profile
+            .map(|p| p.title_order)
+            .filter(|order| order.len() == tokens.len());
+
+        let tokens = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(i, token)| {
+                let lang = match token {
+                    _ if token.is_empty() => None,
+                    // Do not change the order!
+                    _ if RE_JP.is_match(token) => Some(LANG::JP),
+                    _ if RE_CN.is_match(token) => Some(LANG::CN),
+                    _ if RE_EN.is_match(token) => Some(LANG::EN),
+                    _ => None,
+                };
+                // the group's convention only settles what the script leaves
+                // open, and never puts Latin text in a CJK title or back
+                let lang = match (lang, order.map(|o| o[i])) {
+                    (None, Some(hint)) if !token.is_empty() => {
+                        let cjk_hint = matches!(hint, LANG::CN | LANG::JP);
+                        (cjk_hint == RE_CJK_CHAR.is_match(token)).then_some(hint)
+                    }
+                    // kanji alone reads as either
+                    (Some(LANG::CN), Some(LANG::JP)) => Some(LANG::JP),
+                    (lang, _) => lang,
+                };
+                let lang = match lang {
+                    Some(LANG::EN) if romaji_title.unwrap_or_else(|| is_romaji(token)) => Some(LANG::ROMAJI),
+                    lang => lang,
+                };
+                (token.to_owned(), lang)
+            })
+            .collect();
+        Some((raw_title.to_owned(), tokens))
     }
 
     pub fn season(&self) -> u32 {
+        if let Some(season) = self.season_override {
+            return season;
+        }
         match &self.raw_season {
             Some(range) => {
                 let season_tokens: Vec<&str> = RE_SEASON
@@ -172,20 +533,13 @@ impl Parser {
                 }
 
                 for token in season_tokens {
-                    if let Some(m) = RE_SEASON_DIGIT.captures(token).and_then(|c| c.get(1)) {
-                        let str_num = m.as_str();
-                        match str_num.parse() {
-                            Ok(num) => return num,
-                            Err(_) => continue,
-                        }
-                    } else {
-                        if let Some(m) = RE_SEASON_CN_DIGIT.captures(token).and_then(|c| c.get(1)) {
-                            let cn_num = m.as_str();
-                            match cn_to_digit(cn_num) {
-                                Some(num) => return num,
-                                None => continue,
-                            }
-                        }
+                    let num = RE_SEASON_DIGIT
+                        .captures(token)
+                        .or_else(|| RE_SEASON_CN_DIGIT.captures(token))
+                        .and_then(|c| c.get(1))
+                        .and_then(|m| parse_numeral(m.as_str()));
+                    if let Some(num) = num {
+                        return num;
                     }
                 }
                 1
@@ -194,87 +548,369 @@ impl Parser {
         }
     }
 
-    pub fn episode(&self) -> Option<u32> {
+    pub fn episode(&self) -> Option<EpisodeNumber> {
+        self.raw_episode_number().map(|ep| EpisodeNumber {
+            number: self.offset(ep.number),
+            ..ep
+        })
+    }
+
+    fn raw_episode_number(&self) -> Option<EpisodeNumber> {
         match &self.raw_episode {
             Some(range) => {
-                if let Some(cap) = RE_EPISODE.captures(&self.raw[range.to_owned()]) {
-                    if let Ok(ep) = cap[1].parse::<u32>() {
-                        return Some(ep);
+                let raw_episode = &self.raw[range.to_owned()];
+                if let Some(cap) = RE_EPISODE.captures(raw_episode) {
+                    if let Some(number) = parse_numeral(&cap[1]) {
+                        return Some(EpisodeNumber {
+                            number,
+                            fraction: cap.get(2).and_then(|m| parse_hundredths(m.as_str())),
+                        });
                     }
                 }
-                return Some(0);
+                if let Some(number) = RE_EPISODE_CN
+                    .captures(raw_episode)
+                    .and_then(|cap| parse_numeral(&cap[1]))
+                {
+                    return Some(number.into());
+                }
+                // an unnumbered special is the first (and usually only) one
+                match self.kind().is_special() {
+                    true => Some(1.into()),
+                    false => Some(0.into()),
+                }
             }
             None => None,
         }
     }
 
-    pub fn extension(&self) -> Option<String> {
-        match &self.raw_others {
-            Some(range) => {
-                if let Some(m) = RE_EXT
-                    .captures(&self.raw[range.to_owned()])
-                    .and_then(|cap| cap.name("ext"))
-                {
-                    Some(m.as_str().to_owned())
-                } else {
-                    None
+    /// The kind of episode, judged from the episode token first and the
+    /// title second (e.g. `OVA - 02`). Only tags count in the title, so
+    /// `Special A` stays a regular show.
+    pub fn kind(&self) -> EpisodeKind {
+        let episode = self.raw_episode.as_ref().map(|r| &self.raw[r.to_owned()]);
+        let caps = episode.and_then(|e| RE_KIND.captures(e)).or_else(|| {
+            let title = &self.raw[self.raw_season.to_owned()?];
+            kind_tags(title).into_iter().find_map(|tag| RE_KIND.captures(tag))
+        });
+        match caps {
+            Some(caps) if caps.name("recap").is_some() => EpisodeKind::Recap,
+            Some(caps) if caps.name("movie").is_some() => EpisodeKind::Movie,
+            Some(caps) if caps.name("ova").is_some() => EpisodeKind::OVA,
+            Some(caps) if caps.name("oad").is_some() => EpisodeKind::OAD,
+            Some(_) => EpisodeKind::Special,
+            None => EpisodeKind::Regular,
+        }
+    }
+
+    /// Like [`Parser::episode`], but recognizes batch releases covering
+    /// several episodes.
+    pub fn episode_spec(&self) -> Option<EpisodeSpec> {
+        let range = self.raw_episode.as_ref()?;
+        let raw_episode = &self.raw[range.to_owned()];
+        if let Some(caps) = RE_EPISODE_RANGE.captures(raw_episode) {
+            if let (Ok(start), Ok(end)) = (caps[1].parse(), caps[2].parse()) {
+                if start < end {
+                    return Some(EpisodeSpec::Range(self.offset(start), self.offset(end)));
                 }
             }
+        }
+        if let Some(total) = RE_EPISODE_TOTAL
+            .captures(raw_episode)
+            .and_then(|caps| caps[1].parse().ok())
+        {
+            return Some(EpisodeSpec::Range(self.offset(1), self.offset(total)));
+        }
+        if RE_COLLECTION.is_match(raw_episode) {
+            return Some(EpisodeSpec::Collection);
+        }
+        self.episode().map(EpisodeSpec::Single)
+    }
+
+    fn offset(&self, episode: u32) -> u32 {
+        (episode as i64 + self.episode_offset as i64).max(0) as u32
+    }
+
+    /// Year of release, from `(2022)` or `2023年4月` style tags.
+    pub fn year(&self) -> Option<u32> {
+        self.year
+    }
+
+    /// Broadcast quarter, 1 for January shows up to 4 for October ones.
+    pub fn quarter(&self) -> Option<u32> {
+        self.month.filter(|m| (1..=12).contains(m)).map(|m| (m - 1) / 3 + 1)
+    }
+
+    /// Whether the release is marked as the last episode (`END`, `Fin`,
+    /// `完`, `最终话`).
+    pub fn is_final(&self) -> bool {
+        let episode = match &self.raw_episode {
+            Some(range) => &self.raw[range.to_owned()],
+            None => "",
+        };
+        RE_FINAL.is_match(episode) || RE_FINAL.is_match(self.others())
+    }
+
+    /// Part of an episode split across files, e.g. `2` for `Part 2` after
+    /// the episode number.
+    pub fn part(&self) -> Option<u32> {
+        RE_PART
+            .captures(self.others())
+            .and_then(|caps| caps[1].parse().ok())
+    }
+
+    /// CRC32 of the file as tagged by the release, e.g. `[ABCD1234]`. A tag
+    /// of digits only reads as a date like `[20231015]` and is ignored.
+    pub fn crc32(&self) -> Option<u32> {
+        RE_CRC32
+            .captures_iter(self.others())
+            .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+            .map(|tag| tag.as_str())
+            .filter(|tag| !is_date_tag(tag))
+            .last()
+            .and_then(|tag| u32::from_str_radix(tag, 16).ok())
+    }
+
+    /// Revision of the release, either inside the episode token (`[03v2]`)
+    /// or right after it (` - 03v2`).
+    pub fn version(&self) -> Option<u32> {
+        let range = self.raw_episode.as_ref()?;
+        RE_VERSION_INNER
+            .captures(&self.raw[range.to_owned()])
+            .or_else(|| RE_VERSION_AFTER.captures(self.others()))
+            .and_then(|caps| caps[1].parse().ok())
+    }
+
+    pub fn extension(&self) -> Option<String> {
+        match &self.raw_others {
+            Some(range) => RE_EXT
+                .captures(&self.raw[range.to_owned()])
+                .and_then(|cap| cap.name("ext"))
+                .map(|m| m.as_str().to_owned()),
             None => None,
         }
     }
 
+    pub fn resolution(&self) -> Option<u32> {
+        let caps = RE_RESOLUTION.captures(self.others())?;
+        if let Some(m) = caps.get(1).or(caps.get(2)) {
+            return m.as_str().parse().ok();
+        }
+        match caps.get(3)?.as_str().to_uppercase().as_str() {
+            "4K" => Some(2160),
+            "2K" => Some(1440),
+            _ => None,
+        }
+    }
+
+    pub fn video_codec(&self) -> Option<VideoCodec> {
+        let caps = RE_VIDEO_CODEC.captures(self.others())?;
+        match caps {
+            _ if caps.get(1).is_some() => Some(VideoCodec::HEVC),
+            _ if caps.get(2).is_some() => Some(VideoCodec::AVC),
+            _ if caps.get(3).is_some() => Some(VideoCodec::AV1),
+            _ if caps.get(4).is_some() => Some(VideoCodec::VP9),
+            _ => None,
+        }
+    }
+
+    pub fn bit_depth(&self) -> Option<u8> {
+        let caps = RE_BIT_DEPTH.captures(self.others())?;
+        caps.get(1).or(caps.get(2))?.as_str().parse().ok()
+    }
+
+    /// The streaming platform if there is one, otherwise the rip type.
+    pub fn source(&self) -> Option<String> {
+        let others = self.others();
+        RE_SOURCE_PLATFORM
+            .find(others)
+            .or_else(|| RE_SOURCE_RIP.find(others))
+            .map(|m| normalize_source(m.as_str()))
+    }
+
+    pub fn subtitle(&self) -> Subtitle {
+        let others = self.others();
+        let mut languages = Vec::new();
+
+        for caps in RE_SUB_CJK.captures_iter(others) {
+            for c in caps[1].chars() {
+                languages.push(match c {
+                    '简' | '簡' | '中' => SubtitleLang::CHS,
+                    '繁' => SubtitleLang::CHT,
+                    '日' => SubtitleLang::JP,
+                    _ => SubtitleLang::EN,
+                });
+            }
+        }
+        for caps in RE_SUB_LATIN.captures_iter(others) {
+            match caps[1].to_uppercase().as_str() {
+                "CHS" | "GB" => languages.push(SubtitleLang::CHS),
+                "CHT" | "BIG5" => languages.push(SubtitleLang::CHT),
+                "JPSC" => languages.extend([SubtitleLang::JP, SubtitleLang::CHS]),
+                _ => languages.extend([SubtitleLang::JP, SubtitleLang::CHT]),
+            }
+        }
+
+        if languages.is_empty() {
+            languages.extend(self.profile.and_then(|p| p.subtitle));
+        }
+
+        let mut deduped = Vec::new();
+        for lang in languages {
+            if !deduped.contains(&lang) {
+                deduped.push(lang);
+            }
+        }
+
+        let format = RE_SUB_FORMAT.captures(others).and_then(|caps| match caps {
+            _ if caps.get(1).is_some() => Some(SubtitleFormat::Hardsub),
+            _ if caps.get(2).is_some() => Some(SubtitleFormat::Softsub),
+            _ if caps.get(3).is_some() => Some(SubtitleFormat::External),
+            _ => None,
+        });
+
+        Subtitle {
+            languages: deduped,
+            format,
+        }
+    }
+
+    fn others(&self) -> &str {
+        match &self.raw_others {
+            Some(range) => &self.raw[range.to_owned()],
+            None => "",
+        }
+    }
+
     pub fn raw(&self) -> &str {
         &self.raw
     }
 
-    pub fn can_parse(&self) -> bool {
-        match self.raw_episode {
-            Some(_) => true,
-            None => false,
+    pub fn confidence(&self) -> Confidence {
+        let mut warnings = Vec::new();
+        if let Some(range) = &self.raw_episode {
+            // ` 09 ` also matches the common ` - 09 `, which is fine
+            let after_dash = self.raw[..range.start].trim_end().ends_with('-');
+            if RE_BARE_EPISODE.is_match(&self.raw[range.to_owned()]) && !after_dash {
+                warnings.push(ParseWarning::BareEpisodeNumber);
+            }
+        }
+        if !self.raw.starts_with('[') {
+            warnings.push(ParseWarning::NoGroupBracket);
         }
+        if let Some((_, tokens)) = self.title_tokens() {
+            if !tokens.iter().any(|(_, lang)| *lang == Some(LANG::CN)) {
+                warnings.push(ParseWarning::TitleFromFallbackLanguage);
+            }
+            let kept: Vec<&String> = tokens
+                .iter()
+                .filter_map(|(token, lang)| lang.map(|_| token))
+                .collect();
+            if kept.len() == 1 && kept[0].chars().count() <= 3 {
+                warnings.push(ParseWarning::ShortTitle);
+            }
+        }
+        if self.split == "RE_MOVIE_SPLIT" {
+            warnings.push(ParseWarning::MovieFallback);
+        }
+        Confidence::from_warnings(warnings)
     }
 
-    pub fn to_bangumi(self, season: Option<u32>) -> Option<Bangumi> {
+    pub fn can_parse(&self) -> bool {
+        self.raw_episode.is_some()
+    }
+
+    pub fn to_bangumi(self, season: Option<u32>) -> Result<Bangumi, ParseError> {
         match self.can_parse() {
             true => {
                 let group = self.group().unwrap_or("Unknown").to_owned();
-                let Some(title) = self.title() else {
-                    return None;
+                let title = match self.title() {
+                    Some(title) if !title.is_empty() => title,
+                    _ => return Err(ParseError::NoTitle),
                 };
                 let season = match season {
                     Some(s) => s,
                     None => self.season()
                 };
                 // let season = self.season();
-                let episode = self.episode().unwrap_or(0);
-                Some(Bangumi::new(
-                    title,
-                    season,
-                    episode,
-                    group,
-                    self.extension().to_owned(),
-                ))
+                let episode = self.episode_spec().unwrap_or(EpisodeSpec::Single(0.into()));
+                Ok(Bangumi {
+                    resolution: self.resolution(),
+                    video_codec: self.video_codec(),
+                    bit_depth: self.bit_depth(),
+                    source: self.source(),
+                    subtitle: self.subtitle(),
+                    version: self.version(),
+                    kind: self.kind(),
+                    confidence: self.confidence(),
+                    year: self.year(),
+                    crc32: self.crc32(),
+                    is_final: self.is_final(),
+                    part: self.part(),
+                    quarter: self.quarter(),
+                    groups: match self.groups.is_empty() {
+                        true => vec![group.to_owned()],
+                        false => self.groups.to_owned(),
+                    },
+                    ..Bangumi::new(title, season, episode, group, self.extension())
+                })
             }
-            false => None,
+            false => Err(ParseError::NoEpisode),
         }
     }
 }
 
-fn cn_to_digit(cn_digit: &str) -> Option<u32> {
-    match cn_digit {
-        "一" => Some(1),
-        "二" => Some(2),
-        "三" => Some(3),
-        "四" => Some(4),
-        "五" => Some(5),
-        "六" => Some(6),
-        "七" => Some(7),
-        "八" => Some(8),
-        "九" => Some(9),
-        "十" => Some(10),
-        _ => None,
-    }
+/// Parts of a title that may mark its kind: bracketed tags, the last word of
+/// each `/` separated title, and CJK markers like `剧场版` anywhere.
+fn kind_tags(title: &str) -> Vec<&str> {
+    let mut tags: Vec<&str> = RE_BRACKETED
+        .captures_iter(title)
+        .map(|caps| caps.get(1).unwrap().as_str())
+        .collect();
+    tags.extend(title.split('/').filter_map(|t| t.split_whitespace().last()));
+    tags.extend(RE_KIND_CJK.find_iter(title).map(|m| m.as_str()));
+    tags
+}
+
+/// The one or two digits after an episode's decimal point in hundredths,
+/// `None` for a `.0` that adds nothing.
+fn parse_hundredths(digits: &str) -> Option<u32> {
+    let value: u32 = digits.parse().ok()?;
+    let hundredths = match digits.len() {
+        1 => value * 10,
+        _ => value,
+    };
+    (hundredths > 0).then_some(hundredths)
+}
+
+/// Revision tagged anywhere in a file name (`03v2`, `[v2]`), so a release
+/// can be compared with a file renamed earlier.
+pub fn parse_version(name: &str) -> Option<u32> {
+    RE_VERSION_TAG
+        .captures_iter(name)
+        .last()
+        .and_then(|caps| caps[1].parse().ok())
+}
+
+fn normalize_source(source: &str) -> String {
+    let normalized = match source.to_lowercase().replace('-', "").as_str() {
+        "baha" => "Baha",
+        "bglobal" | "bilibili" => "B-Global",
+        "cr" | "crunchyroll" => "CR",
+        "viutv" => "ViuTV",
+        "netflix" | "nf" => "Netflix",
+        "amzn" => "Amazon",
+        "sentai" => "Sentai",
+        "abema" => "ABEMA",
+        "hidive" => "HIDIVE",
+        "disney" | "disney+" => "Disney+",
+        "webrip" => "WebRip",
+        "webdl" => "WEB-DL",
+        "bdrip" | "bdmv" | "bluray" | "bd" => "BD",
+        "dvdrip" | "dvd" => "DVD",
+        "tvrip" | "hdtv" => "TV",
+        _ => source,
+    };
+    normalized.to_owned()
 }
 
 fn split_and_trim<'a>(re: &Regex, s: &'a str) -> Vec<&'a str> {
@@ -290,6 +926,56 @@ fn split_and_trim<'a>(re: &Regex, s: &'a str) -> Vec<&'a str> {
         .collect()
 }
 
+fn broadcast_date(title: &str) -> (Option<u32>, Option<u32>) {
+    if let Some(caps) = RE_YEAR_MONTH.captures(title) {
+        let month = caps.get(2).and_then(|m| parse_numeral(m.as_str()));
+        return (caps[1].parse().ok(), month);
+    }
+    let year = RE_YEAR_BRACKETED
+        .captures(title)
+        .and_then(|caps| caps[1].parse().ok());
+    let month = RE_MONTH_NEW
+        .captures(title)
+        .and_then(|caps| parse_numeral(&caps[1]));
+    (year, month)
+}
+
+/// Whether an 8-digit tag reads as a `YYYYMMDD` date like `[20231006]`,
+/// which is far likelier than a checksum that happens to have no letters.
+fn is_date_tag(tag: &str) -> bool {
+    if !tag.chars().all(|c| c.is_ascii_digit()) || !(tag.starts_with("19") || tag.starts_with("20")) {
+        return false;
+    }
+    let year: u32 = tag[..4].parse().unwrap();
+    let month: u32 = tag[4..6].parse().unwrap();
+    let day: u32 = tag[6..].parse().unwrap();
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// Whether a Latin title reads as romanized Japanese: every word splits
+/// into kana-like syllables, or most do and a particle (`no`, `wa`...) joins
+/// them, as in `Sousou no Frieren`.
+fn is_romaji(title: &str) -> bool {
+    let words: Vec<String> = RE_LATIN_WORD
+        .find_iter(title)
+        .map(|m| m.as_str().to_lowercase())
+        .collect();
+    if words.is_empty() {
+        return false;
+    }
+    let romaji = words.iter().filter(|w| RE_ROMAJI_WORD.is_match(w)).count();
+    let particle = words.iter().skip(1).any(|w| ROMAJI_PARTICLES.contains(&w.as_str()));
+    romaji == words.len() || (particle && romaji * 2 > words.len())
+}
+
 fn join_and_clean(list: Vec<&str>) -> Option<String> {
     if list.is_empty() {
         None
@@ -305,7 +991,7 @@ fn join_and_clean(list: Vec<&str>) -> Option<String> {
 
 #[cfg(test)]
 mod test {
-    use super::Parser;
+    use super::{parse_version, Parser};
 
     #[test]
     fn test_name() {
@@ -318,8 +1004,21 @@ mod test {
     fn test_formatted_name() {
         let p = Parser::new("无职转生，到了异世-界就拿出真本事 第2季 - S02E00 - Skymoon-Raws.mkv".to_owned());
         match p {
-            Some(_) => assert!(false),
-            None => {}
+            Ok(_) => assert!(false),
+            Err(_) => {}
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        let cases = [
+            ("堀与宫村 -piece- - S01E07 - LoliHouse [v3].mkv", Some(3)),
+            ("[Nekomoe kissaten][Jujutsu Kaisen][03v2][1080p][CHS].mp4", Some(2)),
+            ("堀与宫村 -piece- - S01E07 - LoliHouse.mkv", None),
+            ("Lv2 kara Cheat datta Motoyuusha Kouhosha - S01E07 - Group.mkv", None),
+        ];
+        for (name, version) in cases {
+            assert_eq!(version, parse_version(name), "{}", name);
         }
     }
 }
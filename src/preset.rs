@@ -0,0 +1,52 @@
+use std::str::FromStr;
+
+use crate::template::Template;
+
+/// Layouts following the folder conventions of common media servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// `Season 00` for specials, `Title (Year) S01E05`.
+    Jellyfin,
+    /// `Season 00` for specials, `Title (Year) - s01e05 - pt1`.
+    Plex,
+    /// `Specials` folder, `Title S01E05`.
+    Kodi,
+    /// `Specials` folder, `Title - S01E05`.
+    Emby,
+    /// `Season 0` for specials, no year in folder names.
+    Infuse,
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "jellyfin" => Ok(Preset::Jellyfin),
+            "plex" => Ok(Preset::Plex),
+            "kodi" => Ok(Preset::Kodi),
+            "emby" => Ok(Preset::Emby),
+            "infuse" => Ok(Preset::Infuse),
+            other => Err(format!(
+                "unknown preset `{}`, expected jellyfin, plex, kodi, emby or infuse",
+                other
+            )),
+        }
+    }
+}
+
+impl Preset {
+    pub fn source(&self) -> &'static str {
+        match self {
+            Preset::Jellyfin => "{title}< ({year})>/Season {library_season:02}/{title} S{library_season:02}E{episode:02}< - pt{part}>{ext}",
+            Preset::Plex => "{title}< ({year})>/Season {library_season:02}/{title}< ({year})> - s{library_season:02}e{episode:02}< - pt{part}>{ext}",
+            Preset::Kodi => "{title}< ({year})>/{season_dir}/{title} S{library_season:02}E{episode:02}< - pt{part}>{ext}",
+            Preset::Emby => "{title}< ({year})>/{season_dir}/{title} - S{library_season:02}E{episode:02}< - pt{part}>{ext}",
+            Preset::Infuse => "{title}/Season {library_season}/{title} - S{library_season:02}E{episode:02}< - pt{part}>{ext}",
+        }
+    }
+
+    pub fn template(&self) -> Template {
+        Template::parse(self.source()).unwrap()
+    }
+}
@@ -0,0 +1,79 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::bangumi::{SubtitleLang, LANG};
+
+/// The stable naming convention of a fansub group.
+pub struct GroupProfile {
+    pub name: &'static str,
+    /// Dedicated split with `season`, `episode` and `others` captures, tried
+    /// before the generic one.
+    pub split: Option<Regex>,
+    /// Language of each title token by position, used when the token count
+    /// matches to settle tokens the script alone cannot place, like a bare
+    /// `86` or a title written in kanji only.
+    pub title_order: &'static [LANG],
+    /// Subtitle language assumed when the title does not mention one.
+    pub subtitle: Option<SubtitleLang>,
+}
+
+const DASH_SPLIT: &str = r"^\[[^\]]+\]\s*(?P<season>.+?) - (?P<episode>\d+(?:\.\d)?(?:[vV]\d)?|特[別别]篇|[總总]集篇)(?P<others>(?: [\[(]|\.\w+$).*)?$";
+
+lazy_static! {
+    static ref PROFILES: HashMap<&'static str, GroupProfile> = [
+        GroupProfile {
+            name: "ANi",
+            split: Some(Regex::new(DASH_SPLIT).unwrap()),
+            title_order: &[LANG::EN, LANG::CN],
+            subtitle: Some(SubtitleLang::CHT),
+        },
+        GroupProfile {
+            name: "Lilith-Raws",
+            split: Some(Regex::new(DASH_SPLIT).unwrap()),
+            title_order: &[LANG::CN, LANG::EN],
+            subtitle: Some(SubtitleLang::CHT),
+        },
+        GroupProfile {
+            name: "Skymoon-Raws",
+            split: Some(Regex::new(DASH_SPLIT).unwrap()),
+            title_order: &[LANG::CN, LANG::EN],
+            subtitle: Some(SubtitleLang::CHT),
+        },
+        GroupProfile {
+            name: "LoliHouse",
+            split: Some(Regex::new(DASH_SPLIT).unwrap()),
+            title_order: &[LANG::CN, LANG::EN],
+            subtitle: None,
+        },
+        GroupProfile {
+            name: "GJ.Y",
+            split: Some(Regex::new(DASH_SPLIT).unwrap()),
+            title_order: &[LANG::CN, LANG::EN],
+            subtitle: None,
+        },
+        GroupProfile {
+            name: "喵萌奶茶屋",
+            // the bracketed title and episode are what the generic split expects
+            split: None,
+            // simplified / traditional / Japanese / romaji
+            title_order: &[LANG::CN, LANG::CN, LANG::JP, LANG::EN],
+            subtitle: None,
+        },
+    ]
+    .into_iter()
+    .map(|profile| (profile.name, profile))
+    .collect();
+}
+
+/// Finds the profile of a group. For collaborations like `A&LoliHouse` the
+/// members are tried from last to first, as the encoding group usually comes
+/// last and decides the naming.
+pub fn find_profile(group: &str) -> Option<&'static GroupProfile> {
+    PROFILES.get(group).or_else(|| {
+        group
+            .split('&')
+            .rev()
+            .find_map(|member| PROFILES.get(member.trim()))
+    })
+}
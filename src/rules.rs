@@ -0,0 +1,219 @@
+use regex::Regex;
+use std::{collections::HashMap, fs, path::Path};
+use toml::{Table, Value};
+
+use crate::bangumi::{BangumiTitle, LANG};
+use crate::error::RulesError;
+use crate::script::Script;
+use crate::template::Template;
+
+/// User-defined parsing rules, merged with the built-in regexes of
+/// [`crate::parser::Parser`].
+///
+/// ```toml
+/// # tried before the built-in split, must capture `episode`
+/// episode_patterns = ['\[E(?P<episode>\d+)\]']
+/// # must capture `season`
+/// season_patterns = ['(?P<season>\d+)rd Cour']
+/// # removed from the title before anything else, like 新番 and 港澳台
+/// strip_tokens = ['先行版本', 'IPFS服务器种']
+///
+/// # extra spellings of group names, on top of the built-in ones
+/// [group_aliases]
+/// "SweetSub" = "SweetSub字幕组"
+///
+/// # every variant of a series title, in any language, maps to one name
+/// [title_aliases]
+/// "我的百合乃工作是也" = ["百合是我的工作", "Watashi no Yuri wa Oshigoto desu!"]
+///
+/// # output layout, see [`Template`]
+/// template = '{title}/Season {season}/{title} - S{season:02}E{episode:02} - {group}{ext}'
+///
+/// [groups."Skymoon-Raws"]
+/// season = 2
+/// episode_offset = -12
+/// ```
+#[derive(Debug, Default)]
+pub struct Rules {
+    pub episode_patterns: Vec<Regex>,
+    pub season_patterns: Vec<Regex>,
+    pub strip_tokens: Vec<String>,
+    pub group_aliases: HashMap<String, String>,
+    /// Title variant, as returned by [`alias_key`], to canonical series name.
+    pub title_aliases: HashMap<String, String>,
+    pub template: Option<Template>,
+    pub groups: HashMap<String, GroupRules>,
+}
+
+/// Overrides for releases of a single fansub group.
+#[derive(Debug, Default)]
+pub struct GroupRules {
+    pub season: Option<u32>,
+    /// Added to the parsed episode, for groups that number episodes
+    /// continuously across seasons.
+    pub episode_offset: i32,
+    pub episode_patterns: Vec<Regex>,
+    pub strip_tokens: Vec<String>,
+}
+
+impl Rules {
+    pub fn from_file(path: &Path) -> Result<Self, RulesError> {
+        let content = fs::read_to_string(path)?;
+        Self::from_toml(&content)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, RulesError> {
+        let raw: Table = content.parse()?;
+        check_keys(
+            &raw,
+            &[
+                "episode_patterns",
+                "season_patterns",
+                "strip_tokens",
+                "group_aliases",
+                "title_aliases",
+                "template",
+                "groups",
+            ],
+            "",
+        )?;
+
+        let mut groups = HashMap::new();
+        for (name, group) in table(&raw, "groups")?.into_iter().flatten() {
+            let Value::Table(group) = group else {
+                return Err(RulesError::Invalid(format!("`groups.{}` must be a table", name)));
+            };
+            groups.insert(name.to_owned(), GroupRules::from_table(group, &format!("groups.{}.", name))?);
+        }
+
+        let mut group_aliases = HashMap::new();
+        for (alias, group) in table(&raw, "group_aliases")?.into_iter().flatten() {
+            let Value::String(group) = group else {
+                return Err(RulesError::Invalid(format!("`group_aliases.{}` must be a string", alias)));
+            };
+            group_aliases.insert(alias.to_owned(), group.to_owned());
+        }
+
+        let mut title_aliases = HashMap::new();
+        if let Some(aliases) = table(&raw, "title_aliases")? {
+            for canonical in aliases.keys() {
+                let variants = strings(aliases, canonical, "title_aliases.")?;
+                for variant in variants.iter().chain([canonical]) {
+                    title_aliases.insert(alias_key(variant), canonical.to_owned());
+                }
+            }
+        }
+
+        let template = match raw.get("template") {
+            Some(Value::String(source)) => Some(Template::parse(source)?),
+            Some(_) => return Err(RulesError::Invalid("`template` must be a string".to_owned())),
+            None => None,
+        };
+
+        Ok(Rules {
+            episode_patterns: compile(&strings(&raw, "episode_patterns", "")?, "episode")?,
+            season_patterns: compile(&strings(&raw, "season_patterns", "")?, "season")?,
+            strip_tokens: strings(&raw, "strip_tokens", "")?,
+            group_aliases,
+            title_aliases,
+            template,
+            groups,
+        })
+    }
+
+    pub fn group(&self, name: &str) -> Option<&GroupRules> {
+        self.groups.get(name)
+    }
+
+    /// The canonical series name for the first title, in any language,
+    /// found in the alias table.
+    pub fn canonical_title(&self, title: &BangumiTitle) -> Option<&str> {
+        [LANG::CN, LANG::EN, LANG::ROMAJI, LANG::JP]
+            .into_iter()
+            .map(|lang| title.get_title(lang))
+            .chain(title.alternates().iter().map(String::as_str))
+            .filter(|variant| !variant.is_empty())
+            .find_map(|variant| self.title_aliases.get(&alias_key(variant)))
+            .map(String::as_str)
+    }
+}
+
+/// Titles are compared ignoring case, whitespace and Chinese script, so
+/// `Zom 100` and `ZOM100`, or `總集篇` and `总集篇`, are the same variant.
+pub fn alias_key(title: &str) -> String {
+    Script::Simplified
+        .convert(title)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl GroupRules {
+    fn from_table(group: &Table, prefix: &str) -> Result<Self, RulesError> {
+        check_keys(group, &["season", "episode_offset", "episode_patterns", "strip_tokens"], prefix)?;
+        let out_of_range = |key| RulesError::Invalid(format!("`{}{}` is out of range", prefix, key));
+        Ok(GroupRules {
+            season: integer(group, "season", prefix)?
+                .map(|season| season.try_into().map_err(|_| out_of_range("season")))
+                .transpose()?,
+            episode_offset: integer(group, "episode_offset", prefix)?
+                .map(|offset| offset.try_into().map_err(|_| out_of_range("episode_offset")))
+                .transpose()?
+                .unwrap_or(0),
+            episode_patterns: compile(&strings(group, "episode_patterns", prefix)?, "episode")?,
+            strip_tokens: strings(group, "strip_tokens", prefix)?,
+        })
+    }
+}
+
+/// Rejects keys other than `known`, so a typo does not go unnoticed.
+/// `prefix` locates the table in error messages.
+fn check_keys(table: &Table, known: &[&str], prefix: &str) -> Result<(), RulesError> {
+    match table.keys().find(|key| !known.contains(&key.as_str())) {
+        Some(key) => Err(RulesError::Invalid(format!("unknown key `{}{}`", prefix, key))),
+        None => Ok(()),
+    }
+}
+
+fn table<'a>(parent: &'a Table, key: &str) -> Result<Option<&'a Table>, RulesError> {
+    match parent.get(key) {
+        Some(Value::Table(table)) => Ok(Some(table)),
+        Some(_) => Err(RulesError::Invalid(format!("`{}` must be a table", key))),
+        None => Ok(None),
+    }
+}
+
+/// The array of strings under `key`, empty when it is missing.
+fn strings(table: &Table, key: &str, prefix: &str) -> Result<Vec<String>, RulesError> {
+    let invalid = || RulesError::Invalid(format!("`{}{}` must be an array of strings", prefix, key));
+    match table.get(key) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| value.as_str().map(str::to_owned).ok_or_else(invalid))
+            .collect(),
+        Some(_) => Err(invalid()),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn integer(table: &Table, key: &str, prefix: &str) -> Result<Option<i64>, RulesError> {
+    match table.get(key) {
+        Some(Value::Integer(value)) => Ok(Some(*value)),
+        Some(_) => Err(RulesError::Invalid(format!("`{}{}` must be an integer", prefix, key))),
+        None => Ok(None),
+    }
+}
+
+fn compile(patterns: &[String], capture: &'static str) -> Result<Vec<Regex>, RulesError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let re = Regex::new(pattern)?;
+            match re.capture_names().flatten().any(|name| name == capture) {
+                true => Ok(re),
+                false => Err(RulesError::MissingCapture(pattern.to_owned(), capture)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,140 @@
+/// Characters Windows and SMB shares refuse in file names, all ASCII so a
+/// byte offset is also the index of the full-width lookalike.
+const RESERVED_CHARS: &str = "<>:\"/\\|?*";
+const FULLWIDTH_CHARS: &str = "＜＞：＂／＼｜？＊";
+
+/// Device names Windows reserves, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// How generated path components are made safe on every platform.
+#[derive(Debug, Clone)]
+pub struct SanitizePolicy {
+    /// Replace reserved characters with their full-width lookalikes
+    /// (`?` → `？`) instead of removing them.
+    pub fullwidth: bool,
+    /// Longest component in bytes, 255 on most file systems.
+    pub max_bytes: usize,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        SanitizePolicy {
+            fullwidth: false,
+            max_bytes: 255,
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// Makes a single file or folder name safe to create.
+    pub fn apply(&self, component: &str) -> String {
+        truncate(&self.clean(component), self.max_bytes)
+    }
+
+    /// Calls `render` with how many bytes to cut from the end of the title
+    /// until the name it builds fits once sanitized, so a long title gives
+    /// way before the episode tag and extension after it.
+    pub fn fit(&self, render: impl Fn(usize) -> String) -> String {
+        let mut cut = 0;
+        loop {
+            let name = render(cut);
+            let len = self.clean(&name).len();
+            if len <= self.max_bytes || cut >= name.len() {
+                return name;
+            }
+            cut += len - self.max_bytes;
+        }
+    }
+
+    fn clean(&self, component: &str) -> String {
+        let mut cleaned = String::with_capacity(component.len());
+        for c in component.chars() {
+            match RESERVED_CHARS.find(c) {
+                Some(i) if self.fullwidth => cleaned.push(FULLWIDTH_CHARS.chars().nth(i).unwrap()),
+                Some(_) => cleaned.push(' '),
+                None if c.is_control() => (),
+                None => cleaned.push(c),
+            }
+        }
+        // removed characters should not leave double spaces behind
+        let cleaned = cleaned.split(' ').filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" ");
+        // Windows drops trailing dots and spaces, which breaks round trips
+        let mut cleaned = cleaned.trim_end_matches(['.', ' ']).to_owned();
+
+        let stem = cleaned.split('.').next().unwrap_or("").trim();
+        if RESERVED_NAMES.iter().any(|name| name.eq_ignore_ascii_case(stem)) {
+            cleaned.insert(stem.len(), '_');
+        }
+        if cleaned.is_empty() {
+            cleaned.push('_');
+        }
+        cleaned
+    }
+}
+
+/// `text` without its last `bytes` bytes, cut on a character boundary.
+pub(crate) fn cut_end(text: &str, bytes: usize) -> &str {
+    let mut end = text.len().saturating_sub(bytes);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].trim_end()
+}
+
+/// Cuts `name` to `max_bytes` on a character boundary, keeping a short
+/// extension like `.mkv` intact.
+fn truncate(name: &str, max_bytes: usize) -> String {
+    if name.len() <= max_bytes {
+        return name.to_owned();
+    }
+    let ext = match name.rfind('.') {
+        Some(i) if name.len() - i <= 8 && i > 0 => &name[i..],
+        _ => "",
+    };
+    let stem = &name[..name.len() - ext.len()];
+    let mut end = max_bytes.saturating_sub(ext.len()).min(stem.len());
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", stem[..end].trim_end(), ext)
+}
+
+#[cfg(test)]
+mod test {
+    use super::SanitizePolicy;
+
+    #[test]
+    fn test_sanitize() {
+        let policy = SanitizePolicy::default();
+        let cases = [
+            ("Re:Zero kara Hajimeru Isekai Seikatsu", "Re Zero kara Hajimeru Isekai Seikatsu"),
+            ("Fate/Zero - S01E01 - Group.mkv", "Fate Zero - S01E01 - Group.mkv"),
+            ("Who? - S01E01.mkv", "Who - S01E01.mkv"),
+            ("Title...", "Title"),
+            (" ABCD1234.mkv", "ABCD1234.mkv"),
+            ("CON", "CON_"),
+            ("con.mkv", "con_.mkv"),
+            ("Console.mkv", "Console.mkv"),
+            ("tab\there", "tabhere"),
+            ("???", "_"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(expected, policy.apply(raw), "{}", raw);
+        }
+
+        let fullwidth = SanitizePolicy {
+            fullwidth: true,
+            ..SanitizePolicy::default()
+        };
+        assert_eq!("Re：Zero？.mkv", fullwidth.apply("Re:Zero?.mkv"));
+
+        // 3 bytes per character, the extension survives
+        let long = format!("{}.mkv", "葬".repeat(100));
+        let truncated = policy.apply(&long);
+        assert_eq!(format!("{}.mkv", "葬".repeat(83)), truncated);
+        assert!(truncated.len() <= 255);
+    }
+}
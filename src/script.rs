@@ -0,0 +1,114 @@
+use lazy_static::lazy_static;
+use std::{collections::HashMap, str::FromStr};
+
+/// Traditional and simplified forms, two characters per pair.
+const PAIRS: &[&str] = &[
+    "萬万與与醜丑專专業业叢丛東东絲丝兩两嚴严喪丧個个豐丰臨临為为麗丽舉举麼么義义烏乌樂乐喬乔習习鄉乡書书買买亂乱爭争虧亏雲云",
+    "亞亚產产畝亩親亲億亿僅仅從从侖仑倉仓儀仪們们價价眾众優优會会傘伞偉伟傳传傷伤倫伦偽伪體体傭佣僉佥俠侠侶侣僥侥偵侦側侧僑侨",
+    "儈侩儂侬倆俩儉俭債债傾倾僂偻償偿儲储兒儿兌兑黨党蘭兰關关興兴養养獸兽內内岡冈冊册寫写軍军農农馮冯決决況况凍冻淨净準准涼凉",
+    "減减湊凑凜凛鳳凤憑凭凱凯擊击鑿凿劃划劉刘則则剛刚創创刪删別别剎刹劑剂劍剑劇剧勸劝辦办務务動动勵励勁劲勞劳勢势勳勋勻匀區区",
+    "醫医華华協协單单賣卖盧卢衛卫卻却廠厂廳厅歷历厲厉壓压厭厌廁厕廂厢縣县參参雙双變变敘叙疊叠號号嘆叹嚇吓嗎吗啟启吳吴員员聽听",
+    "問问嗚呜鳴鸣啞哑響响喚唤嘩哗喲哟嘯啸嚨咙團团園园圍围圖图圓圆國国聖圣場场壞坏塊块堅坚壇坛壩坝墳坟墜坠壘垒墾垦執执報报塵尘",
+    "墊垫壯壮聲声殼壳壺壶處处備备夠够頭头誇夸夾夹奪夺奮奋獎奖奧奥妝妆婦妇媽妈嫵妩婁娄嬌娇孫孙學学孿孪寧宁寶宝實实寵宠審审憲宪",
+    "寬宽賓宾寢寝對对尋寻導导將将爾尔嘗尝堯尧尷尴屍尸盡尽層层屬属屢屡嶼屿歲岁豈岂嶇岖崗岗嵐岚島岛嶺岭巔巅鞏巩幣币帥帅師师帳帐",
+    "帶带幀帧幫帮廣广莊庄慶庆廬庐庫库應应廟庙龐庞廢废開开異异棄弃張张彌弥彎弯彈弹強强歸归當当錄录徹彻徑径憶忆懺忏憂忧懷怀態态",
+    "慫怂憐怜總总戀恋懇恳惡恶惱恼悅悦懸悬驚惊懼惧慘惨懲惩憊惫慚惭慣惯願愿懾慑戲戏戰战戶户撲扑擴扩掃扫揚扬擾扰撫抚拋抛搶抢護护",
+    "擔担擬拟攏拢揀拣擁拥攔拦擰拧撥拨擇择掛挂摯挚擋挡擠挤揮挥撈捞損损撿捡換换搗捣據据擄掳擲掷擱搁摟搂攪搅攜携攝摄擺摆搖摇攤摊",
+    "撐撑敵敌斂敛數数齋斋斬斩斷断無无舊旧時时曠旷晝昼顯显晉晋曬晒曉晓暈晕暉晖暫暂術术機机殺杀雜杂權权條条來来楊杨傑杰極极構构",
+    "樞枢棗枣櫃柜檸柠棟栋欄栏樹树樣样橋桥檔档夢梦梟枭檢检樓楼橫横櫻樱歡欢歐欧殲歼殘残毀毁毆殴氣气漢汉湯汤溝沟沒没瀝沥淪沦滄沧",
+    "潑泼澤泽潔洁灑洒窪洼濁浊測测濟济瀏浏渾浑濃浓濤涛漲涨澀涩漁渔漸渐滲渗溫温灣湾濕湿潰溃滿满濾滤滯滞濱滨灘滩瀟潇瀾澜瀨濑滅灭",
+    "燈灯靈灵災灾爐炉點点煉炼爛烂燒烧熱热煥焕營营燦灿愛爱爺爷牆墙獨独猶犹獄狱獅狮獵猎貓猫獻献獲获瑪玛環环現现瑣琐璽玺瓊琼電电",
+    "畫画暢畅療疗瘡疮瘋疯癢痒發发盞盏鹽盐監监蓋盖盤盘睜睁瞞瞒礦矿碼码磚砖礎础確确碩硕禮礼禍祸禪禅離离禿秃種种積积稱称穩稳窮穷",
+    "竊窃竅窍窯窑競竞筆笔筍笋箋笺節节築筑簡简籃篮籠笼類类糧粮糾纠紅红紀纪約约級级紡纺紋纹納纳紐纽純纯紗纱紙纸組组細细織织終终",
+    "紹绍經经結结給给絕绝統统綁绑繼继續续維维綠绿綿绵緊紧線线練练緣缘編编緩缓縱纵繪绘縮缩績绩纏缠綺绮緒绪罰罚羅罗罷罢聯联聰聪",
+    "職职聞闻脅胁脈脉膽胆勝胜腦脑腫肿膚肤臉脸艦舰艱艰藝艺蘇苏蘋苹莖茎薦荐藥药蓮莲螢萤蕭萧薩萨蘿萝蕩荡藍蓝蟲虫蝦虾螞蚂蠶蚕蠻蛮",
+    "補补裝装襲袭見见規规覓觅視视覽览覺觉觀观觸触計计訂订認认討讨讓让訓训議议記记講讲許许論论設设訪访證证評评識识詞词試试詩诗",
+    "話话誠诚誤误說说誰谁課课調调談谈請请諸诸讀读謝谢謎谜謊谎謹谨譜谱譯译誕诞豎竖貝贝負负財财貢贡貧贫貨货販贩貪贪責责貴贵貸贷",
+    "費费貿贸賀贺資资賊贼賞赏賢贤賤贱質质賴赖購购賽赛贈赠贏赢趕赶趙赵躍跃蹤踪車车軌轨輕轻較较輔辅輝辉輪轮輸输轉转轟轰辭辞邊边",
+    "遼辽達达遷迁過过邁迈運运還还這这進进遠远違违連连遲迟適适選选遺遗鄧邓鄭郑鄰邻醬酱釋释鋼钢鐵铁銀银銅铜鏡镜錢钱錯错鍋锅鎖锁",
+    "鏈链鑰钥針针釣钓鈴铃鋒锋銳锐鍵键鋸锯鐮镰鏟铲鑽钻鑑鉴長长門门閃闪閉闭間间閒闲閱阅闊阔鬧闹隊队陽阳陰阴陣阵階阶際际陸陆險险",
+    "隱隐隨随隸隶難难雞鸡雛雏雖虽霧雾靂雳靜静韓韩頁页頂顶項项順顺須须預预領领頻频題题額额顏颜顧顾風风飛飞飯饭飲饮餅饼餓饿館馆",
+    "饅馒馬马駕驾騎骑騙骗驗验騰腾驅驱騷骚魚鱼鮮鲜鯨鲸鳥鸟鴉鸦鵝鹅鷹鹰麥麦黃黄齊齐齒齿龍龙龜龟簽签囉啰憤愤槍枪嬪嫔癡痴紛纷紳绅",
+    "衝冲劊刽",
+];
+
+/// Pairs whose simplified form also stands for other characters, like
+/// `髮`/`發` → `发` or `後` → `后`, so they only convert to simplified.
+const ONE_WAY_PAIRS: &[&str] = &[
+    "係系繫系幾几匯汇彙汇復复複复姦奸幹干後后曆历範范裡里裏里製制鐘钟鍾钟鬆松鬥斗隻只臺台檯台颱台髮发麵面嚮向鬱郁遊游週周",
+    "僕仆鍊炼孃娘迴回廻回癒愈籤签餘余佔占",
+];
+
+lazy_static! {
+    static ref TO_SIMPLIFIED: HashMap<char, char> =
+        pairs(PAIRS).into_iter().chain(pairs(ONE_WAY_PAIRS)).collect();
+    static ref TO_TRADITIONAL: HashMap<char, char> =
+        pairs(PAIRS).into_iter().map(|(t, s)| (s, t)).collect();
+}
+
+fn pairs(chunks: &[&str]) -> Vec<(char, char)> {
+    let chars: Vec<char> = chunks.iter().flat_map(|chunk| chunk.chars()).collect();
+    chars.chunks(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Which Chinese script CN titles are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Simplified,
+    Traditional,
+}
+
+impl FromStr for Script {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "simplified" | "hans" | "chs" => Ok(Script::Simplified),
+            "traditional" | "hant" | "cht" => Ok(Script::Traditional),
+            other => Err(format!(
+                "unknown script `{}`, expected simplified or traditional",
+                other
+            )),
+        }
+    }
+}
+
+impl Script {
+    /// Converts character by character with the built-in table, so it works
+    /// offline but does not know about phrases.
+    pub fn convert(&self, text: &str) -> String {
+        let table = match self {
+            Script::Simplified => &*TO_SIMPLIFIED,
+            Script::Traditional => &*TO_TRADITIONAL,
+        };
+        text.chars().map(|c| *table.get(&c).unwrap_or(&c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Script, ONE_WAY_PAIRS, PAIRS};
+
+    #[test]
+    fn test_convert() {
+        for chunk in PAIRS.iter().chain(ONE_WAY_PAIRS) {
+            assert_eq!(0, chunk.chars().count() % 2, "{}", chunk);
+        }
+
+        let cases = [
+            ("總集篇", Script::Simplified, "总集篇"),
+            ("魔法少女小圓", Script::Simplified, "魔法少女小圆"),
+            ("葬送的芙莉蓮", Script::Simplified, "葬送的芙莉莲"),
+            ("頭髮", Script::Simplified, "头发"),
+            // also simplified characters in their own right
+            ("著名", Script::Simplified, "著名"),
+            ("乾杯", Script::Simplified, "乾杯"),
+            ("葬送的芙莉莲", Script::Traditional, "葬送的芙莉蓮"),
+            ("无职转生", Script::Traditional, "無職轉生"),
+            ("Zom 100", Script::Traditional, "Zom 100"),
+        ];
+        for (text, script, expected) in cases {
+            assert_eq!(expected, script.convert(text), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use std::{mem, str::FromStr};
+
+use crate::bangumi::{Bangumi, EpisodeSpec, NamingOptions, LANG};
+use crate::error::TemplateError;
+use crate::sanitize::cut_end;
+
+/// A layout for output paths, replacing the built-in
+/// `Title/Season N/Title - SxxExx - Group.ext`.
+///
+/// ```text
+/// {title_cn}< ({year})>/Season {season:02}/{title_en} - S{season:02}E{episode:02}< [{resolution}]> - {group}{ext}
+/// ```
+///
+/// `/` separates folders, the last segment is the file name. `{name:02}`
+/// pads a number to two digits. A `<...>` section is dropped when one of its
+/// placeholders has no value, so missing data does not leave `[]` behind.
+/// Any other placeholder without a value fails the render.
+///
+/// Placeholders: `title` (by language preference), `series` (`title` plus
+/// the year with `year_in_folder`), `title_cn`, `title_en`, `title_romaji`,
+/// `title_jp`, `season`, `library_season` (0 for specials), `season_dir`,
+/// `episode`, `episode_tag`, `part`, `kind`, `group`, `ext` (with the dot),
+/// `year`, `quarter`, `resolution`, `codec`, `bit_depth`, `source`,
+/// `version` and `crc32`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    segments: Vec<Vec<Part>>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    Field(Field, usize),
+    Optional(Vec<Part>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Series,
+    TitleCn,
+    TitleEn,
+    TitleRomaji,
+    TitleJp,
+    Season,
+    LibrarySeason,
+    SeasonDir,
+    Episode,
+    EpisodeTag,
+    Part,
+    Kind,
+    Group,
+    Ext,
+    Year,
+    Quarter,
+    Resolution,
+    Codec,
+    BitDepth,
+    Source,
+    Version,
+    Crc32,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("title", Field::Title),
+    ("series", Field::Series),
+    ("title_cn", Field::TitleCn),
+    ("title_en", Field::TitleEn),
+    ("title_romaji", Field::TitleRomaji),
+    ("title_jp", Field::TitleJp),
+    ("season", Field::Season),
+    ("library_season", Field::LibrarySeason),
+    ("season_dir", Field::SeasonDir),
+    ("episode", Field::Episode),
+    ("episode_tag", Field::EpisodeTag),
+    ("part", Field::Part),
+    ("kind", Field::Kind),
+    ("group", Field::Group),
+    ("ext", Field::Ext),
+    ("year", Field::Year),
+    ("quarter", Field::Quarter),
+    ("resolution", Field::Resolution),
+    ("codec", Field::Codec),
+    ("bit_depth", Field::BitDepth),
+    ("source", Field::Source),
+    ("version", Field::Version),
+    ("crc32", Field::Crc32),
+];
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        FIELDS.iter().find(|(n, _)| *n == name).map(|&(_, field)| field)
+    }
+
+    fn name(&self) -> &'static str {
+        FIELDS.iter().find(|(_, field)| field == self).map_or("", |&(name, _)| name)
+    }
+
+    fn is_title(&self) -> bool {
+        matches!(
+            self,
+            Field::Title
+                | Field::Series
+                | Field::TitleCn
+                | Field::TitleEn
+                | Field::TitleRomaji
+                | Field::TitleJp
+        )
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Field::Season
+                | Field::LibrarySeason
+                | Field::Episode
+                | Field::Part
+                | Field::Year
+                | Field::Quarter
+                | Field::BitDepth
+                | Field::Version
+        )
+    }
+
+    /// The value for `bangumi`, `None` when it is unknown or empty.
+    fn value(&self, bangumi: &Bangumi, options: &NamingOptions, width: usize) -> Option<String> {
+        let number = |n: u32| format!("{:0width$}", n, width = width);
+        let title = |lang: LANG| match options.strip_year(bangumi.title.get_title(lang)).as_ref() {
+            "" => None,
+            title if lang == LANG::CN => Some(match options.script {
+                Some(script) => script.convert(title),
+                None => title.to_owned(),
+            }),
+            title => Some(title.to_owned()),
+        };
+        let value = match self {
+            Field::Title => Some(bangumi.title.get_named_title(options)),
+            Field::Series => Some(bangumi.series_dir(options)),
+            Field::TitleCn => title(LANG::CN),
+            Field::TitleEn => title(LANG::EN),
+            Field::TitleRomaji => title(LANG::ROMAJI),
+            Field::TitleJp => title(LANG::JP),
+            Field::Season => Some(number(bangumi.season)),
+            Field::LibrarySeason => Some(number(bangumi.library_season())),
+            Field::SeasonDir => Some(bangumi.season_dir()),
+            Field::Episode => match bangumi.episode {
+                EpisodeSpec::Single(ep) => Some(format!("{:0width$}", ep, width = width)),
+                EpisodeSpec::Range(start, end) => Some(format!("{}-{}", number(start), number(end))),
+                EpisodeSpec::Collection => None,
+            },
+            Field::EpisodeTag => Some(bangumi.episode_tag()),
+            Field::Part => bangumi.part.map(number),
+            Field::Kind => Some(bangumi.kind.to_string()),
+            Field::Group => Some(bangumi.group.to_owned()),
+            Field::Ext => bangumi.extension.to_owned(),
+            Field::Year => bangumi.year.map(number),
+            Field::Quarter => bangumi.quarter.map(number),
+            Field::Resolution => bangumi.resolution.map(|r| format!("{}p", r)),
+            Field::Codec => bangumi.video_codec.map(|c| c.to_string()),
+            Field::BitDepth => bangumi.bit_depth.map(|b| number(b as u32)),
+            Field::Source => bangumi.source.to_owned(),
+            Field::Version => bangumi.version.map(number),
+            Field::Crc32 => bangumi.crc32.map(|crc| format!("{:08X}", crc)),
+        };
+        value.filter(|v| !v.is_empty())
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        // the innermost `<...>` section is last
+        let mut stack: Vec<Vec<Part>> = vec![Vec::new()];
+        let mut text = String::new();
+        let mut chars = source.chars();
+
+        while let Some(c) = chars.next() {
+            if matches!(c, '{' | '<' | '>' | '/') && !text.is_empty() {
+                stack.last_mut().unwrap().push(Part::Text(mem::take(&mut text)));
+            }
+            match c {
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(TemplateError::Unbalanced('{')),
+                        }
+                    }
+                    stack.last_mut().unwrap().push(parse_placeholder(&spec)?);
+                }
+                '}' => return Err(TemplateError::Unbalanced('}')),
+                '<' => stack.push(Vec::new()),
+                '>' => {
+                    if stack.len() == 1 {
+                        return Err(TemplateError::Unbalanced('>'));
+                    }
+                    let section = stack.pop().unwrap();
+                    stack.last_mut().unwrap().push(Part::Optional(section));
+                }
+                '/' if stack.len() > 1 => return Err(TemplateError::Unbalanced('<')),
+                '/' => segments.push(take_segment(&mut stack[0])?),
+                c => text.push(c),
+            }
+        }
+
+        if stack.len() > 1 {
+            return Err(TemplateError::Unbalanced('<'));
+        }
+        if !text.is_empty() {
+            stack[0].push(Part::Text(text));
+        }
+        segments.push(take_segment(&mut stack[0])?);
+
+        Ok(Template {
+            source: source.to_owned(),
+            segments,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether a `{year}` placeholder appears anywhere.
+    pub fn uses_year(&self) -> bool {
+        self.segments.iter().any(|parts| has_field(parts, Field::Year))
+    }
+
+    /// Renders each path segment, the file name last. Fails on the first
+    /// placeholder outside `<...>` that has no value. Titles in a file name
+    /// too long for `options.sanitize` are shortened, the rest is kept.
+    pub fn render(&self, bangumi: &Bangumi, options: &NamingOptions) -> Result<Vec<String>, TemplateError> {
+        let mut segments = self
+            .segments
+            .iter()
+            .map(|parts| match render_parts(parts, bangumi, options, 0) {
+                (rendered, None) => Ok(rendered),
+                (_, Some(field)) => Err(TemplateError::MissingValue(field.name().to_owned())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if let (Some(name), Some(parts)) = (segments.last_mut(), self.segments.last()) {
+            *name = options
+                .sanitize
+                .fit(|cut| render_parts(parts, bangumi, options, cut).0);
+        }
+        Ok(segments)
+    }
+}
+
+fn take_segment(parts: &mut Vec<Part>) -> Result<Vec<Part>, TemplateError> {
+    match parts.is_empty() {
+        true => Err(TemplateError::EmptySegment),
+        false => Ok(mem::take(parts)),
+    }
+}
+
+fn parse_placeholder(spec: &str) -> Result<Part, TemplateError> {
+    let (name, format) = match spec.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format)),
+        None => (spec.trim(), None),
+    };
+    let field =
+        Field::from_name(name).ok_or_else(|| TemplateError::UnknownPlaceholder(name.to_owned()))?;
+    let width = match format {
+        None => 0,
+        Some(format) if field.is_number() => format
+            .parse()
+            .map_err(|_| TemplateError::InvalidFormat(spec.to_owned()))?,
+        Some(_) => return Err(TemplateError::InvalidFormat(spec.to_owned())),
+    };
+    Ok(Part::Field(field, width))
+}
+
+fn has_field(parts: &[Part], field: Field) -> bool {
+    parts.iter().any(|part| match part {
+        Part::Text(_) => false,
+        Part::Field(f, _) => *f == field,
+        Part::Optional(section) => has_field(section, field),
+    })
+}
+
+/// The rendered text with `cut` bytes taken off each title, and the first
+/// placeholder in it without a value.
+fn render_parts(
+    parts: &[Part],
+    bangumi: &Bangumi,
+    options: &NamingOptions,
+    cut: usize,
+) -> (String, Option<Field>) {
+    let mut rendered = String::new();
+    let mut missing = None;
+    for part in parts {
+        match part {
+            Part::Text(text) => rendered.push_str(text),
+            Part::Field(field, width) => match field.value(bangumi, options, *width) {
+                Some(value) if field.is_title() => rendered.push_str(cut_end(&value, cut)),
+                Some(value) => rendered.push_str(&value),
+                None => missing = missing.or(Some(*field)),
+            },
+            Part::Optional(section) => {
+                if let (text, None) = render_parts(section, bangumi, options, cut) {
+                    rendered.push_str(&text);
+                }
+            }
+        }
+    }
+    (rendered, missing)
+}
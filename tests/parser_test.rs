@@ -1,16 +1,30 @@
-use auto_bangumi_rs::parser::Parser;
+use auto_bangumi_rs::{
+    bangumi::{
+        Bangumi, BangumiTitle, EpisodeKind, EpisodeNumber, EpisodeSpec, NamingOptions, ParseWarning, SubtitleFormat,
+        SubtitleLang, VideoCodec, LANG,
+    },
+    error::{ParseError, RulesError, TemplateError},
+    parser::{ParseResult, Parser},
+    preset::Preset,
+    profile::find_profile,
+    rules::Rules,
+    sanitize::SanitizePolicy,
+    script::Script,
+    template::Template,
+};
 use regex::Regex;
 use rss::Channel;
+use std::path::PathBuf;
 
 #[test]
 fn test_parser() {
     for title in get_titles() {
         let parsed = Parser::new(title.0.to_owned()).and_then(|parser| parser.to_bangumi(None));
-        assert!(parsed.is_some());
+        assert!(parsed.is_ok());
         let parsed = parsed.unwrap();
         println!("{}", parsed);
         assert_eq!(title.2, parsed.season);
-        assert_eq!(title.3, parsed.episode);
+        assert_eq!(EpisodeSpec::Single(title.3.into()), parsed.episode);
         assert_eq!(title.4, parsed.group);
         assert_eq!(title.1, parsed.title.get_default_title());
     }
@@ -47,7 +61,7 @@ async fn test_url(url: &str) {
                     continue;
                 }
                 println!("- {}", raw_title);
-                let result = Parser::new(raw_title).and_then(|p| p.title());
+                let result = Parser::new(raw_title).ok().and_then(|p| p.title());
                 println!("{}", result.as_ref().unwrap());
                 assert!(result.is_some());
             }
@@ -77,7 +91,7 @@ fn test_parser_season() {
     for title in get_titles() {
         let parser = Parser::new(title.0.to_owned());
         println!("- {}", title.0);
-        assert_eq!(true, parser.is_some_and(|p| p.season() == title.2));
+        assert_eq!(true, parser.is_ok_and(|p| p.season() == title.2));
     }
 }
 
@@ -87,7 +101,8 @@ fn test_parser_episode() {
         let parser = Parser::new(title.0.to_owned()).unwrap();
         println!("- {}", title.0);
         assert!(parser.episode().is_some());
-        assert_eq!(title.3, parser.episode().unwrap());
+        assert_eq!(title.3, parser.episode().unwrap().number);
+        assert_eq!(None, parser.episode().unwrap().fraction);
     }
 }
 
@@ -115,6 +130,821 @@ fn test_parser_group() {
     }
 }
 
+#[test]
+fn test_parser_group_alias() {
+    let cases = [
+        ("[喵萌Production] 葬送的芙莉莲 [05][1080p][简日双语]", "喵萌奶茶屋", vec!["喵萌奶茶屋"]),
+        ("[Nekomoe kissaten] Sousou no Frieren - 05 [1080p]", "喵萌奶茶屋", vec!["喵萌奶茶屋"]),
+        (
+            "[LoliHouse&喵萌] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC]",
+            "LoliHouse&喵萌奶茶屋",
+            vec!["LoliHouse", "喵萌奶茶屋"],
+        ),
+        (
+            "[北宇治字幕組×LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p]",
+            "北宇治字幕组&LoliHouse",
+            vec!["北宇治字幕组", "LoliHouse"],
+        ),
+    ];
+    for (raw, group, groups) in cases {
+        let bangumi = Parser::new(raw.to_owned()).unwrap().to_bangumi(None).unwrap();
+        assert_eq!(group, bangumi.group, "{}", raw);
+        assert_eq!(groups, bangumi.groups, "{}", raw);
+    }
+
+    let rules = Rules::from_toml("[group_aliases]\n\"SweetSub\" = \"SweetSub字幕组\"").unwrap();
+    let parser = Parser::with_rules("[SweetSub] 葬送的芙莉莲 - 05 [1080p]".to_owned(), &rules).unwrap();
+    assert_eq!(Some("SweetSub字幕组"), parser.group());
+}
+
+#[test]
+fn test_parser_title_alias() {
+    let rules = Rules::from_toml(
+        "[title_aliases]\n\"我的百合乃工作是也\" = [\"百合是我的工作\", \"Watashi no Yuri wa Oshigoto desu!\"]",
+    )
+    .unwrap();
+    let titles = [
+        "[LoliHouse] 百合是我的工作 / Watashi no Yuri wa Oshigoto desu! - 03 [WebRip 1080p HEVC-10bit AAC]",
+        "[ANi] 我的百合乃工作是也 - 03 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+        "[Nekomoe kissaten] WATASHI NO YURI WA OSHIGOTO DESU! - 03 [1080p]",
+    ];
+    for title in titles {
+        let bangumi = Parser::with_rules(title.to_owned(), &rules)
+            .and_then(|p| p.to_bangumi(None))
+            .unwrap();
+        assert_eq!(Some("我的百合乃工作是也"), bangumi.title.canonical(), "{}", title);
+        assert_eq!(
+            PathBuf::from("/anime/我的百合乃工作是也/Season 1"),
+            bangumi
+                .gen_fullpath(&PathBuf::from("/anime"), true, &NamingOptions::default())
+                .parent().unwrap()
+        );
+    }
+
+    let parser = Parser::new(titles[0].to_owned()).unwrap();
+    assert_eq!("百合是我的工作", parser.title().unwrap().get_default_title());
+}
+
+#[test]
+fn test_parser_title_lang() {
+    let bangumi = Parser::new("[LoliHouse] 百合是我的工作 / Watashi no Yuri wa Oshigoto desu! - 03 [WebRip 1080p HEVC-10bit AAC].mkv".to_owned())
+        .and_then(|p| p.to_bangumi(None))
+        .unwrap();
+    let dest = PathBuf::from("/anime");
+    let cases = [
+        (vec![LANG::CN, LANG::EN, LANG::JP], "/anime/百合是我的工作/Season 1/百合是我的工作 - S01E03 - LoliHouse.mkv"),
+        (vec![LANG::ROMAJI, LANG::JP, LANG::CN], "/anime/Watashi no Yuri wa Oshigoto desu!/Season 1/Watashi no Yuri wa Oshigoto desu! - S01E03 - LoliHouse.mkv"),
+        // no JP title, falls back to the default order
+        (vec![LANG::JP], "/anime/百合是我的工作/Season 1/百合是我的工作 - S01E03 - LoliHouse.mkv"),
+    ];
+    for (title_langs, expected) in cases {
+        let options = NamingOptions {
+            title_langs,
+            ..NamingOptions::default()
+        };
+        assert_eq!(PathBuf::from(expected), bangumi.gen_fullpath(&dest, true, &options));
+    }
+
+    assert_eq!(Ok(LANG::JP), "ja".parse());
+    assert!("fr".parse::<LANG>().is_err());
+}
+
+#[test]
+fn test_parser_romaji() {
+    let cases = [
+        (
+            "[LoliHouse] 药屋少女的呢喃 / Kusuriya no Hitorigoto / The Apothecary Diaries - 05 [WebRip 1080p HEVC-10bit AAC]",
+            "The Apothecary Diaries", "Kusuriya no Hitorigoto",
+        ),
+        (
+            "[LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC]",
+            "", "Sousou no Frieren",
+        ),
+        (
+            "[ANi] Andeddo Gaaru Maadaafarusu - 不死少女的谋杀闹剧 - 09 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+            "", "Andeddo Gaaru Maadaafarusu",
+        ),
+        (
+            "[GJ.Y] 僵尸百分百～变成僵尸之前想做的100件事～ / Zom 100 - 07 (B-Global 3840x2160 HEVC AAC MKV)",
+            "Zom 100", "",
+        ),
+        (
+            "[LoliHouse] 来自深渊 / Made in Abyss - 01 [BDRip 1080p HEVC-10bit FLAC]",
+            "Made in Abyss", "",
+        ),
+    ];
+    for (raw, en, romaji) in cases {
+        let title = Parser::new(raw.to_owned()).unwrap().title().unwrap();
+        assert_eq!(en, title.get_title(LANG::EN), "{}", raw);
+        assert_eq!(romaji, title.get_title(LANG::ROMAJI), "{}", raw);
+    }
+
+    let title = Parser::new(cases[0].0.to_owned()).unwrap().title().unwrap();
+    assert_eq!(
+        vec!["药屋少女的呢喃", "Kusuriya no Hitorigoto", "The Apothecary Diaries"],
+        title.alternates()
+    );
+}
+
+#[test]
+fn test_parser_year() {
+    let cases = [
+        ("[SubsPlease] Urusei Yatsura (2022) - 05 (1080p).mkv", "Urusei Yatsura (2022)", Some(2022), None),
+        ("[Nekomoe kissaten][Urusei Yatsura (2022)][05][1080p][CHS].mp4", "Urusei Yatsura (2022)", Some(2022), None),
+        ("[猎户不鸽压制] 僵尸百分百 Zom 100 [05] [1080p] [繁日内嵌] [2023年7月番]", "僵尸百分百 Zom 100", Some(2023), Some(3)),
+        ("【喵萌奶茶屋】★04月新番★[百合是我的工作！/Watashi no Yuri wa Oshigoto desu!][03][1080p][简日双语]", "百合是我的工作！", None, Some(2)),
+        ("【动漫国字幕组】★10月新番[堀与宫村 -piece-][11][720P][繁体][MP4]", "堀与宫村 -piece-", None, Some(4)),
+        ("[LoliHouse] AYAKA ‐绫岛奇谭‐ - 12 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕][END]", "AYAKA ‐绫岛奇谭‐", None, None),
+    ];
+    for (raw, title, year, quarter) in cases {
+        let parser = Parser::new(raw.to_owned()).unwrap();
+        assert_eq!(title, parser.title().unwrap().get_default_title(), "{}", raw);
+        assert_eq!(year, parser.year(), "{}", raw);
+        assert_eq!(quarter, parser.quarter(), "{}", raw);
+    }
+
+    let bangumi = Parser::new(cases[0].0.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+    let options = NamingOptions {
+        year_in_folder: true,
+        ..NamingOptions::default()
+    };
+    assert_eq!(
+        PathBuf::from("/anime/Urusei Yatsura (2022)/Season 1/Urusei Yatsura - S01E05 - SubsPlease.mkv"),
+        bangumi.gen_fullpath(&PathBuf::from("/anime"), true, &options)
+    );
+}
+
+#[test]
+fn test_parser_crc32() {
+    let cases = [
+        ("[SubsPlease] Urusei Yatsura (2022) - 05 (1080p) [ABCD1234].mkv", Some(0xABCD1234)),
+        ("[Erai-raws] Sousou no Frieren - 05 [1080p][Multiple Subtitle][3f0a9c1e].mkv", Some(0x3F0A9C1E)),
+        ("[LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC].mkv", None),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [20231006].mkv", None),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [20240229].mkv", None),
+        // digits only, but no date
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [58213904].mkv", Some(0x58213904)),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [20231306].mkv", Some(0x20231306)),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [20230229].mkv", Some(0x20230229)),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234).mkv", None),
+    ];
+    for (raw, crc32) in cases {
+        let bangumi = Parser::new(raw.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+        assert_eq!(crc32, bangumi.crc32, "{}", raw);
+        // an 8-digit tag is never the episode
+        assert_eq!(EpisodeSpec::Single(5.into()), bangumi.episode, "{}", raw);
+    }
+
+    let title = Parser::new(cases[0].0.to_owned()).unwrap().title().unwrap();
+    assert_eq!("Urusei Yatsura (2022)", title.get_default_title());
+}
+
+#[test]
+fn test_parser_final() {
+    let cases = [
+        ("[LoliHouse] AYAKA ‐绫岛奇谭‐ - 12 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕][END]", true),
+        ("【喵萌奶茶屋】★07月新番★[无职转生 2期 / Mushoku Tensei S2][12END][1080p][简日双语]", true),
+        ("[桜都字幕组] 葬送的芙莉莲 / Sousou no Frieren [28][完][1080p][简繁内封]", true),
+        ("[北宇治字幕组] 葬送的芙莉莲 第28话 最终话 [1080p][简日内嵌]", true),
+        ("[SubsPlease] Sousou no Frieren - 28 Fin (1080p) [ABCD1234].mkv", true),
+        ("[ANi] 无职转生～到了异世界就拿出真本事 第二季 - 08 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]", false),
+        ("[LoliHouse] The End of the World - 05 [WebRip 1080p HEVC-10bit AAC]", false),
+    ];
+    for (raw, is_final) in cases {
+        let bangumi = Parser::new(raw.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+        assert_eq!(is_final, bangumi.is_final, "{}", raw);
+    }
+}
+
+#[test]
+fn test_template() {
+    let bangumi = Parser::new("[SubsPlease] Urusei Yatsura (2022) - 05 (1080p) [ABCD1234].mkv".to_owned())
+        .and_then(|p| p.to_bangumi(None))
+        .unwrap();
+    let dest = PathBuf::from("/anime");
+    let cases = [
+        (
+            "{title} ({year})/Season {season:02}/{title_romaji} - S{season:02}E{episode:02} [{resolution}] - {group}{ext}",
+            "/anime/Urusei Yatsura (2022)/Season 01/Urusei Yatsura - S01E05 [1080p] - SubsPlease.mkv",
+        ),
+        (
+            "{title}/{title_en}<[{codec}]>< {crc32}>{ext}",
+            // no EN title, the default layout is used
+            "/anime/Urusei Yatsura (2022)/Season 1/Urusei Yatsura (2022) - S01E05 - SubsPlease.mkv",
+        ),
+        (
+            "{series}/{season_dir}/{episode_tag}{ext}",
+            "/anime/Urusei Yatsura (2022)/Season 1/S01E05.mkv",
+        ),
+    ];
+    for (source, expected) in cases {
+        let options = NamingOptions {
+            template: Some(Template::parse(source).unwrap()),
+            ..NamingOptions::default()
+        };
+        assert_eq!(PathBuf::from(expected), bangumi.gen_fullpath(&dest, true, &options), "{}", source);
+        assert_eq!(
+            PathBuf::from(expected).file_name().unwrap().to_str().unwrap(),
+            bangumi.gen_filename(&options)
+        );
+    }
+
+    let template = Template::parse("{title}/{title_en}{ext}").unwrap();
+    assert_eq!(
+        Err(TemplateError::MissingValue("title_en".to_owned())),
+        template.render(&bangumi, &NamingOptions::default())
+    );
+    let options = NamingOptions {
+        template: Some(template),
+        ..NamingOptions::default()
+    };
+    assert_eq!(
+        Err(TemplateError::MissingValue("title_en".to_owned())),
+        bangumi.try_gen_fullpath(&dest, true, &options)
+    );
+    assert_eq!(
+        Err(TemplateError::MissingValue("title_en".to_owned())),
+        bangumi.try_gen_filename(&options)
+    );
+
+    let errors = [
+        ("{title}/{titel}{ext}", TemplateError::UnknownPlaceholder("titel".to_owned())),
+        ("{title:02}", TemplateError::InvalidFormat("title:02".to_owned())),
+        ("{season:x}", TemplateError::InvalidFormat("season:x".to_owned())),
+        ("{title", TemplateError::Unbalanced('{')),
+        ("<[{codec}]", TemplateError::Unbalanced('<')),
+        ("{title}>", TemplateError::Unbalanced('>')),
+        ("{title}//{group}", TemplateError::EmptySegment),
+    ];
+    for (source, error) in errors {
+        assert_eq!(error, Template::parse(source).unwrap_err(), "{}", source);
+    }
+
+    let rules = Rules::from_toml("template = '{title} - {episode:03}{ext}'").unwrap();
+    assert_eq!("{title} - {episode:03}{ext}", rules.template.unwrap().source());
+    assert!(matches!(
+        Rules::from_toml("template = '{nope}'"),
+        Err(RulesError::Template(TemplateError::UnknownPlaceholder(_)))
+    ));
+}
+
+#[test]
+fn test_sanitize_path() {
+    let title = BangumiTitle::new(None, Some("Re:Zero / Starting Life in Another World?".to_owned()), None);
+    let bangumi = Bangumi::new(title, 1, EpisodeSpec::Single(3.into()), "LoliHouse".to_owned(), Some(".mkv".to_owned()));
+    let cases = [
+        (false, "/anime/Re Zero Starting Life in Another World/Season 1/Re Zero Starting Life in Another World - S01E03 - LoliHouse.mkv"),
+        (true, "/anime/Re：Zero ／ Starting Life in Another World？/Season 1/Re：Zero ／ Starting Life in Another World？ - S01E03 - LoliHouse.mkv"),
+    ];
+    for (fullwidth, expected) in cases {
+        let options = NamingOptions {
+            sanitize: SanitizePolicy {
+                fullwidth,
+                ..SanitizePolicy::default()
+            },
+            ..NamingOptions::default()
+        };
+        assert_eq!(PathBuf::from(expected), bangumi.gen_fullpath(&PathBuf::from("/anime"), true, &options));
+    }
+
+    // a title too long for one name gives way, episodes stay apart
+    let title = BangumiTitle::new(Some("葬".repeat(84)), None, None);
+    let episode = |number: u32, version: Option<u32>| Bangumi {
+        version,
+        ..Bangumi::new(title.clone(), 1, EpisodeSpec::Single(number.into()), "LoliHouse".to_owned(), Some(".mkv".to_owned()))
+    };
+    let jellyfin = NamingOptions {
+        template: Some(Preset::Jellyfin.template()),
+        ..NamingOptions::default()
+    };
+    let cases = [
+        (NamingOptions::default(), " - S01E05 - LoliHouse.mkv", " - S01E06 - LoliHouse [v2].mkv"),
+        (jellyfin, " S01E05.mkv", " S01E06.mkv"),
+    ];
+    for (options, first, second) in cases {
+        let first_name = episode(5, None).gen_filename(&options);
+        let second_name = episode(6, Some(2)).gen_filename(&options);
+        assert!(first_name.starts_with("葬葬") && first_name.ends_with(first), "{}", first_name);
+        assert!(second_name.starts_with("葬葬") && second_name.ends_with(second), "{}", second_name);
+        assert!(first_name.len() <= 255 && second_name.len() <= 255);
+        assert!(first_name.len() > 240, "{}", first_name);
+    }
+}
+
+#[test]
+fn test_parse_result() {
+    let rules = Rules::default();
+    let result = ParseResult::new("[SubsPlease] Urusei Yatsura (2022) - 05 (1080p) [ABCD1234].mkv", &rules);
+    assert_eq!(None, result.error);
+    assert_eq!(Some(2022), result.bangumi.unwrap().year);
+
+    let result = ParseResult::new("Urusei Yatsura - S01E05 - SubsPlease.mkv", &rules);
+    assert_eq!(None, result.bangumi);
+    assert_eq!(Some(ParseError::AlreadyFormatted), result.error);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let rules = Rules::default();
+    for title in get_titles() {
+        let result = ParseResult::new(title.0, &rules);
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(result, serde_json::from_str::<ParseResult>(&json).unwrap(), "{}", title.0);
+    }
+
+    let result = ParseResult::new("[ANi] 86 - 不存在的战区 - 01 [1080P][Baha][WEB-DL][AAC AVC][MP4]", &rules);
+    let json = serde_json::to_value(&result).unwrap();
+    let bangumi = &json["bangumi"];
+    assert_eq!("ANi", bangumi["group"]);
+    assert_eq!(
+        serde_json::json!({"type": "single", "episodes": {"number": 1, "fraction": null}}),
+        bangumi["episode"]
+    );
+    let range = serde_json::to_value(EpisodeSpec::Range(1, 12)).unwrap();
+    assert_eq!(serde_json::json!({"type": "range", "episodes": [1, 12]}), range);
+    let collection = serde_json::to_value(EpisodeSpec::Collection).unwrap();
+    assert_eq!(serde_json::json!({"type": "collection"}), collection);
+    assert_eq!("不存在的战区", bangumi["title"]["cn"]);
+    assert_eq!(serde_json::json!(["CHT"]), bangumi["subtitle"]["languages"]);
+    assert_eq!(serde_json::Value::Null, json["error"]);
+}
+
+#[test]
+fn test_preset() {
+    let regular = "[SubsPlease] Urusei Yatsura (2022) - 05 Part 2 (1080p) [ABCD1234].mkv";
+    let special = "[LoliHouse] 福星小子 / Urusei Yatsura OVA 01 [WebRip 1080p HEVC-10bit AAC].mkv";
+    let cases = [
+        (
+            Preset::Jellyfin,
+            "/anime/Urusei Yatsura (2022)/Season 01/Urusei Yatsura S01E05 - pt2.mkv",
+            "/anime/福星小子/Season 00/福星小子 S00E01.mkv",
+        ),
+        (
+            Preset::Plex,
+            "/anime/Urusei Yatsura (2022)/Season 01/Urusei Yatsura (2022) - s01e05 - pt2.mkv",
+            "/anime/福星小子/Season 00/福星小子 - s00e01.mkv",
+        ),
+        (
+            Preset::Kodi,
+            "/anime/Urusei Yatsura (2022)/Season 1/Urusei Yatsura S01E05 - pt2.mkv",
+            "/anime/福星小子/Specials/福星小子 S00E01.mkv",
+        ),
+        (
+            Preset::Emby,
+            "/anime/Urusei Yatsura (2022)/Season 1/Urusei Yatsura - S01E05 - pt2.mkv",
+            "/anime/福星小子/Specials/福星小子 - S00E01.mkv",
+        ),
+        (
+            Preset::Infuse,
+            "/anime/Urusei Yatsura (2022)/Season 1/Urusei Yatsura (2022) - S01E05 - pt2.mkv",
+            "/anime/福星小子/Season 0/福星小子 - S00E01.mkv",
+        ),
+    ];
+    for (preset, regular_path, special_path) in cases {
+        let options = NamingOptions {
+            template: Some(preset.template()),
+            ..NamingOptions::default()
+        };
+        for (raw, expected) in [(regular, regular_path), (special, special_path)] {
+            let bangumi = Parser::new(raw.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+            assert_eq!(
+                PathBuf::from(expected),
+                bangumi.gen_fullpath(&PathBuf::from("/anime"), true, &options)
+            );
+        }
+    }
+    assert_eq!(Ok(Preset::Plex), "Plex".parse());
+}
+
+#[test]
+fn test_parser_script() {
+    let titles = [
+        "[Lilith-Raws] 葬送的芙莉蓮 / Sousou no Frieren - 05 [Baha][WEB-DL][1080p][AVC AAC][CHT][MP4].mp4",
+        "[LoliHouse] 葬送的芙莉莲 / Sousou no Frieren - 05 [WebRip 1080p HEVC-10bit AAC].mp4",
+    ];
+    let cases = [
+        (Script::Simplified, "/anime/葬送的芙莉莲/Season 1"),
+        (Script::Traditional, "/anime/葬送的芙莉蓮/Season 1"),
+    ];
+    for (script, expected) in cases {
+        let options = NamingOptions {
+            script: Some(script),
+            ..NamingOptions::default()
+        };
+        for title in titles {
+            let bangumi = Parser::new(title.to_owned()).and_then(|p| p.to_bangumi(None)).unwrap();
+            let path = bangumi.gen_fullpath(&PathBuf::from("/anime"), true, &options);
+            assert_eq!(PathBuf::from(expected), path.parent().unwrap(), "{}", title);
+        }
+    }
+
+    // aliases match either script
+    let rules = Rules::from_toml("[title_aliases]\n\"芙莉莲\" = [\"葬送的芙莉莲\"]").unwrap();
+    let parser = Parser::with_rules(titles[0].to_owned(), &rules).unwrap();
+    assert_eq!(Some("芙莉莲"), parser.title().unwrap().canonical());
+}
+
+#[test]
+fn test_parser_media_info() {
+    let cases = [
+        (
+            "[ANi] 无职转生～到了异世界就拿出真本事 第二季 - 08 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+            Some(1080), Some(VideoCodec::AVC), None, Some("Baha"),
+        ),
+        (
+            "[GJ.Y] 僵尸百分百～变成僵尸之前想做的100件事～ / Zom 100 - 07 (B-Global 3840x2160 HEVC AAC MKV)",
+            Some(2160), Some(VideoCodec::HEVC), None, Some("B-Global"),
+        ),
+        (
+            "[LoliHouse] AYAKA ‐绫岛奇谭‐ - 12 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕][END]",
+            Some(1080), Some(VideoCodec::HEVC), Some(10), Some("WebRip"),
+        ),
+        (
+            "【动漫国字幕组】★07月新番[堀与宫村 -piece-][11][720P][繁体][MP4]",
+            Some(720), None, None, None,
+        ),
+    ];
+    for (title, resolution, codec, bit_depth, source) in cases {
+        let parser = Parser::new(title.to_owned()).unwrap();
+        assert_eq!(resolution, parser.resolution(), "{}", title);
+        assert_eq!(codec, parser.video_codec(), "{}", title);
+        assert_eq!(bit_depth, parser.bit_depth(), "{}", title);
+        assert_eq!(source, parser.source().as_deref(), "{}", title);
+    }
+}
+
+#[test]
+fn test_parser_subtitle() {
+    use SubtitleLang::*;
+    let cases = [
+        (
+            "【喵萌奶茶屋】★07月新番★[无职转生 2期 / Mushoku Tensei S2][00][1080p][简日双语][招募翻译]",
+            vec![CHS, JP], None,
+        ),
+        (
+            "[喵萌奶茶屋&LoliHouse] SYNDUALITY Noir - 09 [WebRip 1080p HEVC-10bit AAC][简繁日内封字幕]",
+            vec![CHS, CHT, JP], Some(SubtitleFormat::Softsub),
+        ),
+        (
+            "[动漫国字幕组&LoliHouse] 打工吧!! 魔王大人 / Hataraku Maou-sama S2 - 19 [WebRip 1080p HEVC-10bit AAC][简繁外挂字幕]",
+            vec![CHS, CHT], Some(SubtitleFormat::External),
+        ),
+        (
+            "[猎户不鸽压制] 僵尸百分百 Zom 100 [05] [1080p] [繁日内嵌] [2023年7月番]",
+            vec![CHT, JP], Some(SubtitleFormat::Hardsub),
+        ),
+        (
+            "【动漫国字幕组】★07月新番[堀与宫村 -piece-][11][720P][繁体][MP4]",
+            vec![CHT], None,
+        ),
+        (
+            "[ANi] 无职转生～到了异世界就拿出真本事 第二季 - 08 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+            vec![CHT], None,
+        ),
+        (
+            "【悠哈璃羽字幕社】[死神千年血战诀别谭_Bleach - Thousand-Year Blood War][22][1080p][CHS]",
+            vec![CHS], None,
+        ),
+    ];
+    for (title, languages, format) in cases {
+        let subtitle = Parser::new(title.to_owned()).unwrap().subtitle();
+        assert_eq!(languages, subtitle.languages, "{}", title);
+        assert_eq!(format, subtitle.format, "{}", title);
+    }
+}
+
+#[test]
+fn test_parser_version() {
+    let cases = [
+        ("[Nekomoe kissaten][Jujutsu Kaisen][03v2][1080p][CHS].mp4", 3, Some(2)),
+        ("[LoliHouse] 堀与宫村 -piece- - 07v3 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv", 7, Some(3)),
+        ("[LoliHouse] 堀与宫村 -piece- - 07 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv", 7, None),
+    ];
+    for (title, episode, version) in cases {
+        let bangumi = Parser::new(title.to_owned()).unwrap().to_bangumi(None).unwrap();
+        assert_eq!(EpisodeSpec::Single(episode.into()), bangumi.episode, "{}", title);
+        assert_eq!(version, bangumi.version, "{}", title);
+        assert_eq!(version.is_some(), bangumi.is_revision(), "{}", title);
+    }
+
+    let bangumi = Parser::new(cases[1].0.to_owned()).unwrap().to_bangumi(None).unwrap();
+    assert_eq!(
+        "堀与宫村 -piece- - S01E07 - LoliHouse [v3].mkv",
+        bangumi.gen_filename(&NamingOptions::default())
+    );
+}
+
+#[test]
+fn test_parser_batch() {
+    let cases = [
+        (
+            "[Lilith-Raws] 堀与宫村 -piece- / Horimiya：Piece [01-13][Baha][WebDL 1080p AVC AAC][CHT]",
+            "堀与宫村 -piece-", EpisodeSpec::Range(1, 13),
+        ),
+        (
+            "[LoliHouse] AYAKA ‐绫岛奇谭‐ [01-12 Fin][WebRip 1080p HEVC-10bit AAC][简繁内封字幕]",
+            "AYAKA ‐绫岛奇谭‐", EpisodeSpec::Range(1, 12),
+        ),
+        (
+            "[ANi] 僵尸 100～在成为僵尸前要做的 100 件事～ - 01-12 [1080P][Baha][WEB-DL][AAC AVC][CHT]",
+            "僵尸 100～在成为僵尸前要做的 100 件事～", EpisodeSpec::Range(1, 12),
+        ),
+        (
+            "[桜都字幕组] 堀与宫村 -piece- / Horimiya Piece [合集][1080p][简繁内封]",
+            "堀与宫村 -piece-", EpisodeSpec::Collection,
+        ),
+    ];
+    for (title, name, spec) in cases {
+        let bangumi = Parser::new(title.to_owned()).unwrap().to_bangumi(None).unwrap();
+        assert_eq!(name, bangumi.title.get_default_title(), "{}", title);
+        assert_eq!(spec, bangumi.episode, "{}", title);
+        assert!(bangumi.episode.is_batch(), "{}", title);
+    }
+}
+
+#[test]
+fn test_parser_special() {
+    let cases = [
+        (
+            "[ANi] 无职转生～到了异世界就拿出真本事 第二季 - 特别篇 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+            "无职转生～到了异世界就拿出真本事", EpisodeKind::Special, 1,
+            "无职转生～到了异世界就拿出真本事/Specials/无职转生～到了异世界就拿出真本事 - S00E01 - Special S02 - ANi",
+        ),
+        (
+            "[LoliHouse] 堀与宫村 -piece- [SP2][WebRip 1080p HEVC-10bit AAC][简繁内封字幕]",
+            "堀与宫村 -piece-", EpisodeKind::Special, 2,
+            "堀与宫村 -piece-/Specials/堀与宫村 -piece- - S00E02 - Special - LoliHouse",
+        ),
+        (
+            "[Lilith-Raws] 打工吧，魔王大人！ / Hataraku Maou-sama! OVA - 02 [Baha][WEB-DL][1080p][AVC AAC][CHT]",
+            "打工吧，魔王大人！", EpisodeKind::OVA, 2,
+            "打工吧，魔王大人！/Specials/打工吧，魔王大人！ - S00E02 - OVA - Lilith-Raws",
+        ),
+        (
+            "[桜都字幕组] 堀与宫村 [总集篇][1080p][简繁内封]",
+            "堀与宫村", EpisodeKind::Recap, 1,
+            "堀与宫村/Specials/堀与宫村 - S00E01 - Recap - 桜都字幕组",
+        ),
+        (
+            "[LoliHouse] 剧场版 紫罗兰永恒花园 / Violet Evergarden the Movie [BDRip 1080p HEVC-10bit FLAC]",
+            "紫罗兰永恒花园", EpisodeKind::Movie, 1,
+            "紫罗兰永恒花园/Specials/紫罗兰永恒花园 - S00E01 - Movie - LoliHouse",
+        ),
+        // a kind word that is part of the title
+        (
+            "[DBD-Raws] Special A - 05 [1080P][BDRip][HEVC-10bit][FLAC]",
+            "Special A", EpisodeKind::Regular, 5,
+            "Special A/Season 1/Special A - S01E05 - DBD-Raws",
+        ),
+    ];
+    for (title, name, kind, episode, path) in cases {
+        let bangumi = Parser::new(title.to_owned()).unwrap().to_bangumi(None).unwrap();
+        assert_eq!(name, bangumi.title.get_default_title(), "{}", title);
+        assert_eq!(kind, bangumi.kind, "{}", title);
+        assert_eq!(EpisodeSpec::Single(episode.into()), bangumi.episode, "{}", title);
+        assert_eq!(
+            PathBuf::from(path),
+            bangumi.gen_fullpath(&PathBuf::new(), true, &NamingOptions::default())
+        );
+    }
+}
+
+#[test]
+fn test_parser_fractional_episode() {
+    let cases = [
+        (
+            "[LoliHouse] 堀与宫村 -piece- - 12.5 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+            12, Some(50), "堀与宫村 -piece- - S01E12.5 - LoliHouse.mkv",
+        ),
+        (
+            "[桜都字幕组] 堀与宫村 -piece- / Horimiya Piece [06.5][1080p][简繁内封].mp4",
+            6, Some(50), "堀与宫村 -piece- - S01E06.5 - 桜都字幕组.mp4",
+        ),
+        (
+            "[LoliHouse] 堀与宫村 -piece- - 12.05 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+            12, Some(5), "堀与宫村 -piece- - S01E12.05 - LoliHouse.mkv",
+        ),
+        (
+            "[LoliHouse] 堀与宫村 -piece- - 12.10 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+            12, Some(10), "堀与宫村 -piece- - S01E12.1 - LoliHouse.mkv",
+        ),
+        (
+            "[LoliHouse] 堀与宫村 -piece- - 12.0 [WebRip 1080p HEVC-10bit AAC][简繁内封字幕].mkv",
+            12, None, "堀与宫村 -piece- - S01E12 - LoliHouse.mkv",
+        ),
+    ];
+    for (title, number, fraction, filename) in cases {
+        let bangumi = Parser::new(title.to_owned()).unwrap().to_bangumi(None).unwrap();
+        let episode = EpisodeNumber { number, fraction };
+        assert_eq!(EpisodeSpec::Single(episode), bangumi.episode, "{}", title);
+        assert_eq!(filename, bangumi.gen_filename(&NamingOptions::default()), "{}", title);
+    }
+
+    // 12.05 < 12.1 < 12.5 < 13
+    let episodes: Vec<EpisodeNumber> = [(12, Some(5)), (12, Some(10)), (12, Some(50)), (13, None)]
+        .into_iter()
+        .map(|(number, fraction)| EpisodeNumber { number, fraction })
+        .collect();
+    assert!(episodes.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_parser_numerals() {
+    let cases = [
+        ("[ANi] 某科学的超电磁炮 第十二季 - 03 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]", "某科学的超电磁炮", 12, 3),
+        ("[ANi] 某科学的超电磁炮 第两季 - 03 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]", "某科学的超电磁炮", 2, 3),
+        ("[Lilith-Raws] 某科学的超电磁炮 第２期 - 04 [Baha][WebDL 1080p AVC AAC][CHT]", "某科学的超电磁炮", 2, 4),
+        ("[Lilith-Raws] 某科学的超电磁炮 第弐期 - 04 [Baha][WebDL 1080p AVC AAC][CHT]", "某科学的超电磁炮", 2, 4),
+        ("[动漫国字幕组] 某科学的超电磁炮 第三部 第二十话 [1080P][简体][MP4]", "某科学的超电磁炮", 3, 20),
+        ("[动漫国字幕组] 某科学的超电磁炮 第十一话 [1080P][简体][MP4]", "某科学的超电磁炮", 1, 11),
+    ];
+    for (title, name, season, episode) in cases {
+        let bangumi = Parser::new(title.to_owned()).unwrap().to_bangumi(None).unwrap();
+        assert_eq!(name, bangumi.title.get_default_title(), "{}", title);
+        assert_eq!(season, bangumi.season, "{}", title);
+        assert_eq!(EpisodeSpec::Single(episode.into()), bangumi.episode, "{}", title);
+    }
+}
+
+#[test]
+fn test_parser_errors() {
+    let cases = [
+        (
+            "无职转生，到了异世-界就拿出真本事 第2季 - S02E00 - Skymoon-Raws.mkv",
+            ParseError::AlreadyFormatted,
+        ),
+        ("[Skymoon-Raws] 无职转生 [ViuTV][WEB-RIP][1080p]", ParseError::NoEpisode),
+        ("[Skymoon-Raws] - 11 [ViuTV][WEB-RIP][1080p]", ParseError::NoTitle),
+    ];
+    for (title, error) in cases {
+        let result = Parser::new(title.to_owned()).and_then(|p| p.to_bangumi(None));
+        assert_eq!(Some(error), result.err(), "{}", title);
+    }
+    assert_eq!(
+        Some(ParseError::NotAFile),
+        Parser::from_path(&PathBuf::from("/nonexistent/[ANi] Test - 01.mkv")).err()
+    );
+}
+
+#[test]
+fn test_parser_explain() {
+    let parser = Parser::with_trace("【喵萌奶茶屋】★07月新番★[无职转生 2期 / Mushoku Tensei S2][00][1080p][简日双语][招募翻译]".to_owned(), &Rules::default()).unwrap();
+    let trace = parser.explain();
+    assert_eq!("raw", trace.stages[0].0);
+    assert!(trace.stages.iter().any(|(name, _)| *name == "RE_BANGUMI_CHARS"));
+    assert_eq!("RE_MAIN_SPLIT", trace.split);
+    assert_eq!("[00]", trace.episode.unwrap().1);
+    assert_eq!(
+        vec![
+            ("无职转生".to_owned(), Some(LANG::CN)),
+            ("".to_owned(), None),
+            ("Mushoku Tensei".to_owned(), Some(LANG::ROMAJI)),
+        ],
+        trace.title_tokens
+    );
+}
+
+#[test]
+fn test_parser_confidence() {
+    let cases = [
+        (
+            "[Lilith-Raws] 不死少女的谋杀闹剧 / Undead Girl Murder Farce - 09 [Baha][WebDL 1080p AVC AAC][CHT]",
+            1.0,
+            vec![],
+        ),
+        (
+            "[GJ.Y] Hataraku Maou-sama!! - 21 (CR 1920x1080 AVC AAC MKV)",
+            1.0,
+            vec![ParseWarning::TitleFromFallbackLanguage],
+        ),
+        (
+            "魔王 12 1080p.mkv",
+            0.25,
+            vec![
+                ParseWarning::BareEpisodeNumber,
+                ParseWarning::NoGroupBracket,
+                ParseWarning::ShortTitle,
+            ],
+        ),
+    ];
+    for (title, score, warnings) in cases {
+        let confidence = Parser::new(title.to_owned()).unwrap().confidence();
+        assert!((score - confidence.score).abs() < 1e-4, "{}: {}", title, confidence.score);
+        assert_eq!(warnings, confidence.warnings, "{}", title);
+    }
+}
+
+#[test]
+fn test_parser_rules() {
+    let rules = Rules::from_toml(
+        r#"
+        episode_patterns = ['#(?P<episode>\d+)']
+        season_patterns = ['(?P<season>\d+)rd Cour']
+        strip_tokens = ['先行版本']
+
+        [groups."Skymoon-Raws"]
+        season = 2
+        episode_offset = -12
+        strip_tokens = ['IPFS服务器种']
+        "#,
+    )
+    .unwrap();
+
+    let cases = [
+        ("[Foo] 打工吧，魔王大人！ #05 [1080p]", "打工吧，魔王大人！", 1, 5),
+        ("[Foo] 打工吧，魔王大人！ 3rd Cour - 05 [1080p]", "打工吧，魔王大人！", 3, 5),
+        (
+            "[Skymoon-Raws] 无职转生，到了异世界就拿出真本事 - 23 [ViuTV][WEB-RIP][1080p][AVC AAC][CHT][SRT][MKV](先行版本) IPFS服务器种",
+            "无职转生，到了异世界就拿出真本事", 2, 11,
+        ),
+    ];
+    for (title, name, season, episode) in cases {
+        let parser = Parser::with_rules(title.to_owned(), &rules).unwrap();
+        assert!(!parser.raw().contains("先行版本"), "{}", title);
+        let bangumi = parser.to_bangumi(None).unwrap();
+        assert_eq!(name, bangumi.title.get_default_title(), "{}", title);
+        assert_eq!(season, bangumi.season, "{}", title);
+        assert_eq!(EpisodeSpec::Single(episode.into()), bangumi.episode, "{}", title);
+    }
+
+    // optional captures that take no part fall through to the next pattern
+    let rules = Rules::from_toml(
+        r#"
+        episode_patterns = ['#(?P<episode>\d+)?$']
+        season_patterns = ['(?:(?P<season>\d+)rd)? Cour']
+        "#,
+    )
+    .unwrap();
+    let cases = [
+        ("[Foo] Bar Cour - 05 [1080p]", 1, 5),
+        ("[Foo] Bar 3rd Cour - 05 [1080p]", 3, 5),
+        ("[Foo] Bar - 05 #", 1, 5),
+    ];
+    for (title, season, episode) in cases {
+        let bangumi = Parser::with_rules(title.to_owned(), &rules).and_then(|p| p.to_bangumi(None)).unwrap();
+        assert_eq!(season, bangumi.season, "{}", title);
+        assert_eq!(EpisodeSpec::Single(episode.into()), bangumi.episode, "{}", title);
+    }
+
+    assert!(matches!(
+        Rules::from_toml("episode_patterns = ['#\\d+']"),
+        Err(RulesError::MissingCapture(..))
+    ));
+    let invalid = [
+        "unknown = 1",
+        "strip_tokens = '先行版本'",
+        "[groups.Foo]\nseason = 'two'",
+        "[groups.Foo]\nseason = -1",
+        "[groups.Foo]\nepisode_offsets = 12",
+        "[title_aliases]\n\"芙莉莲\" = \"葬送的芙莉莲\"",
+    ];
+    for content in invalid {
+        assert!(matches!(Rules::from_toml(content), Err(RulesError::Invalid(_))), "{}", content);
+    }
+}
+
+#[test]
+fn test_parser_profile() {
+    assert_eq!("LoliHouse", find_profile("喵萌奶茶屋&LoliHouse").unwrap().name);
+    assert_eq!("ANi", find_profile("ANi").unwrap().name);
+    assert_eq!("喵萌奶茶屋", find_profile("喵萌奶茶屋").unwrap().name);
+    assert!(find_profile("TEST").is_none());
+
+    let parser = Parser::new("[ANi] 86 - 不存在的战区 - 01 [1080P][Baha][WEB-DL][AAC AVC][MP4]".to_owned()).unwrap();
+    assert_eq!("GroupProfile.split", parser.explain().split);
+    assert_eq!(vec![SubtitleLang::CHT], parser.subtitle().languages);
+    let title = parser.title().unwrap();
+    assert_eq!("86", title.get_title(LANG::EN));
+    assert_eq!("不存在的战区", title.get_title(LANG::CN));
+    assert_eq!(Some(1.into()), parser.episode());
+
+    // the order only settles what the script leaves open
+    let cases = [
+        (
+            "[LoliHouse] Sword Art Online Alicization - War of Underworld - 12 [WebRip 1080p HEVC-10bit AAC]",
+            "Sword Art Online Alicization War of Underworld", "",
+        ),
+        (
+            "[LoliHouse] Kaguya-sama wa Kokurasetai - First Kiss wa Owaranai - 01 [WebRip 1080p HEVC-10bit AAC]",
+            "Kaguya-sama wa Kokurasetai First Kiss wa Owaranai", "",
+        ),
+        (
+            "[ANi] 葬送的芙莉莲 - Sousou no Frieren - 28 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
+            "葬送的芙莉莲", "",
+        ),
+        (
+            "【喵萌奶茶屋】★10月新番★[咒术回战 / 咒術迴戰 / 呪術廻戦 / Jujutsu Kaisen][03][1080p][简日双语]",
+            "咒术回战 咒術迴戰", "呪術廻戦",
+        ),
+    ];
+    for (raw, name, jp) in cases {
+        let title = Parser::new(raw.to_owned()).unwrap().title().unwrap();
+        assert_eq!(name, title.get_default_title(), "{}", raw);
+        assert_eq!(jp, title.get_title(LANG::JP), "{}", raw);
+    }
+}
+
 fn get_titles() -> Vec<(&'static str, &'static str, u32, u32, &'static str)> {
     vec![
                 (
@@ -247,7 +1077,8 @@ fn get_titles() -> Vec<(&'static str, &'static str, u32, u32, &'static str)> {
                 ),
                 (
                     "[ANi] 无职转生～到了异世界就拿出真本事 第二季 - 特别篇 [1080P][Baha][WEB-DL][AAC AVC][CHT][MP4]",
-                    "无职转生～到了异世界就拿出真本事", 2, 0, "ANi",
+                    // an unnumbered special is the first of the specials, not episode 0
+                    "无职转生～到了异世界就拿出真本事", 2, 1, "ANi",
                 ),
                 (
                     "【喵萌奶茶屋】★07月新番★[无职转生 2期 / Mushoku Tensei S2][00][1080p][简日双语][招募翻译]",